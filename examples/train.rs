@@ -0,0 +1,119 @@
+// Trains a paddle controller with the cross-entropy method, about the simplest learning that
+// gets anywhere: a linear policy over a few of the ball and paddle features, whose weights are sampled
+// around a mean that moves towards the best scoring samples every generation.
+//
+//     cargo run --release --example train
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rust_breakout::env::{Action, BreakoutEnv, EnvConfig, Observation};
+
+const GENERATIONS: usize = 12;
+const POPULATION: usize = 16;
+const ELITE: usize = 4;
+// games every candidate plays, fewer lucky bounces decide which are best
+const GAMES: u64 = 2;
+// how far the ball is across from the paddle, which way it is going across and a bias
+const INPUTS: usize = 3;
+const WEIGHTS: usize = INPUTS * Action::ALL.len();
+const MAX_STEPS: u32 = 1000;
+
+struct LinearPolicy {
+    weights: Vec<f32>,
+}
+
+impl LinearPolicy {
+    fn act(&self, observation: &Observation) -> Action {
+        let inputs = [observation[0] - observation[4], observation[2], 1.0];
+        let value = |action: usize| -> f32 {
+            let weights = &self.weights[action * INPUTS..(action + 1) * INPUTS];
+            weights.iter().zip(inputs).map(|(w, x)| w * x).sum()
+        };
+        let best = (0..Action::ALL.len())
+            .max_by(|&a, &b| value(a).total_cmp(&value(b)))
+            .unwrap_or(0);
+        Action::from_index(best)
+    }
+}
+
+fn run_episode(env: &mut BreakoutEnv, policy: &LinearPolicy, seed: u64) -> f32 {
+    let mut observation = env.reset(seed);
+    let mut total = 0.0;
+    loop {
+        let (next, reward, done) = env.step(policy.act(&observation));
+        total += reward;
+        if done {
+            return total;
+        }
+        observation = next;
+    }
+}
+
+// Standard normal samples, by the Box-Muller transform
+fn gaussian(rng: &mut StdRng) -> f32 {
+    let u: f32 = rng.gen_range(f32::EPSILON..1.0);
+    let v: f32 = rng.gen();
+    (-2.0 * u.ln()).sqrt() * (std::f32::consts::TAU * v).cos()
+}
+
+fn main() {
+    let mut env = BreakoutEnv::new(EnvConfig {
+        max_steps: Some(MAX_STEPS),
+        ..Default::default()
+    })
+    .expect("a classic game can be played");
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut mean = vec![0.0; WEIGHTS];
+    let mut deviation = vec![1.0; WEIGHTS];
+
+    for generation in 0..GENERATIONS {
+        // every candidate plays the same games, so they are judged on their play alone
+        let seeds = generation as u64 * GAMES..(generation as u64 + 1) * GAMES;
+        let mut results: Vec<(f32, Vec<f32>)> = (0..POPULATION)
+            .map(|_| {
+                let weights: Vec<f32> = mean
+                    .iter()
+                    .zip(&deviation)
+                    .map(|(mean, deviation)| mean + deviation * gaussian(&mut rng))
+                    .collect();
+                let policy = LinearPolicy { weights };
+                let reward = seeds
+                    .clone()
+                    .map(|seed| run_episode(&mut env, &policy, seed))
+                    .sum::<f32>();
+                (reward / GAMES as f32, policy.weights)
+            })
+            .collect();
+        results.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+        let elite = &results[..ELITE];
+        for i in 0..WEIGHTS {
+            let values = elite.iter().map(|(_, weights)| weights[i]);
+            mean[i] = values.clone().sum::<f32>() / ELITE as f32;
+            let variance =
+                values.map(|value| (value - mean[i]).powi(2)).sum::<f32>() / ELITE as f32;
+            // a little extra noise keeps the search from settling too early
+            deviation[i] = variance.sqrt() + 0.05;
+        }
+
+        let average = results.iter().map(|(reward, _)| reward).sum::<f32>() / POPULATION as f32;
+        println!(
+            "generation {:2}: average reward {:7.1}, best {:7.1}",
+            generation + 1,
+            average,
+            results[0].0
+        );
+    }
+
+    // the learnt policy on games it hasn't seen
+    let policy = LinearPolicy { weights: mean };
+    for seed in 1000..1005 {
+        let reward = run_episode(&mut env, &policy, seed);
+        println!(
+            "trained policy, seed {}: reward {:.1}, score {} on level {}",
+            seed,
+            reward,
+            env.score(),
+            env.level()
+        );
+    }
+}
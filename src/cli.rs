@@ -0,0 +1,349 @@
+use std::path::PathBuf;
+
+use clap::{error::ErrorKind, CommandFactory, Parser};
+
+#[cfg(feature = "spectator")]
+use crate::spectator::DEFAULT_SPECTATOR_PORT;
+use crate::{
+    game::prelude::{
+        Autoplayer, BotSkill, BrickGrid, Difficulty, DifficultyParams, GameMode, LevelGenerator,
+        LevelLayout, Pattern, SpeedUpConfig,
+    },
+    netcode::{NetOptions, NetRequest, NetRole, DEFAULT_PORT},
+    replay::Replay,
+};
+
+const MIN_WINDOW_WIDTH: f32 = 640.0;
+const MIN_WINDOW_HEIGHT: f32 = 480.0;
+
+/// Breakout!! - break all the bricks without letting the ball get past the paddle.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Args {
+    /// Width of the window in logical pixels
+    #[arg(long, default_value_t = 800.0, value_parser = parse_width)]
+    pub width: f32,
+
+    /// Height of the window in logical pixels
+    #[arg(long, default_value_t = 600.0, value_parser = parse_height)]
+    pub height: f32,
+
+    /// Start in borderless fullscreen instead of a window
+    #[arg(long)]
+    pub fullscreen: bool,
+
+    /// Game mode to play
+    #[arg(long, value_enum, default_value_t = GameMode::Classic)]
+    pub mode: GameMode,
+
+    /// Level to start the game on
+    #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    pub level: u32,
+
+    /// Seed for the random number generator, picked at random when omitted
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// How hard the game should be (custom uses the values from settings.ron)
+    #[arg(long, value_enum, default_value_t = Difficulty::Normal)]
+    pub difficulty: Difficulty,
+
+    /// Play back a replay recorded with --record (uses the replay's seed, level and difficulty)
+    #[arg(
+        long,
+        value_name = "FILE",
+        conflicts_with_all = ["mode", "seed", "level", "difficulty", "record", "level_file"]
+    )]
+    pub replay: Option<PathBuf>,
+
+    /// Record the paddle inputs of this session to a replay file
+    #[arg(long, value_name = "FILE")]
+    pub record: Option<PathBuf>,
+
+    /// Run the game without a window as fast as possible, exiting when the game is over
+    #[arg(long)]
+    pub headless: bool,
+
+    /// End a --headless run after this many seconds of game time, however the game is going
+    #[arg(long, value_name = "SECONDS", requires = "headless", value_parser = parse_non_negative)]
+    pub time_limit: Option<f32>,
+
+    /// Start a game straight away instead of showing the main menu
+    #[arg(long)]
+    pub skip_menu: bool,
+
+    /// Play the bricks from a level file made in the level editor on the starting level
+    #[arg(long, value_name = "FILE")]
+    pub level_file: Option<PathBuf>,
+
+    /// Open the level editor, on the --level-file if one is given
+    #[arg(long, conflicts_with_all = ["replay", "headless", "skip_menu"])]
+    pub editor: bool,
+
+    /// Shape of generated levels, a random one for every level when omitted
+    #[arg(long, value_enum)]
+    pub pattern: Option<Pattern>,
+
+    /// Text spelled out one letter per level by the text pattern
+    #[arg(long, value_name = "TEXT")]
+    pub pattern_text: Option<String>,
+
+    /// Image turned into bricks by the image pattern, its opaque or else its dark parts
+    #[arg(long, value_name = "FILE")]
+    pub pattern_image: Option<PathBuf>,
+
+    /// Let a bot play for PLAYER (1 or 2, repeat for both), e.g. to run the game unattended
+    #[arg(
+        long,
+        value_name = "PLAYER",
+        value_parser = clap::value_parser!(u8).range(1..=2),
+        conflicts_with_all = ["replay", "host", "join"]
+    )]
+    pub bot: Vec<u8>,
+
+    /// Seconds a bot takes to react to the ball changing direction (from settings.ron by default)
+    #[arg(long, value_name = "SECONDS", value_parser = parse_non_negative)]
+    pub bot_reaction: Option<f32>,
+
+    /// Most pixels a bot misjudges where the ball will be by (from settings.ron by default)
+    #[arg(long, value_name = "PIXELS", value_parser = parse_non_negative)]
+    pub bot_error: Option<f32>,
+
+    /// Host a versus game over the network and wait for another player to join
+    #[arg(long, conflicts_with_all = ["replay", "editor", "export_level", "join"])]
+    pub host: bool,
+
+    /// Join a versus game hosted at ADDRESS (a host name or IP, with or without the port)
+    #[arg(long, value_name = "ADDRESS", conflicts_with_all = ["replay", "editor", "export_level"])]
+    pub join: Option<String>,
+
+    /// UDP port to host network games on, and to join them on when the address has none
+    #[arg(long, default_value_t = DEFAULT_PORT)]
+    pub port: u16,
+
+    /// Frames between pressing a key and the paddle moving in network games, which hides up to
+    /// that much latency
+    #[arg(long, value_name = "FRAMES", default_value_t = 3, value_parser = clap::value_parser!(u8).range(..=30))]
+    pub input_delay: u8,
+
+    /// Local port the spectator server streams the game state on
+    #[cfg(feature = "spectator")]
+    #[arg(long, value_name = "PORT", default_value_t = DEFAULT_SPECTATOR_PORT)]
+    pub spectator_port: u16,
+
+    /// Write a generated level to a level file and exit (uses --seed, --level and --pattern)
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["replay", "editor", "headless"])]
+    pub export_level: Option<PathBuf>,
+}
+
+// Everything needed to launch the game, after the arguments have been validated
+pub struct LaunchOptions {
+    pub args: Args,
+    pub replay: Option<Replay>,
+    pub layout: Option<LevelLayout>,
+    pub level_generator: LevelGenerator,
+}
+
+impl LaunchOptions {
+    // Parses the command line, exiting with a usage error if anything is invalid
+    pub fn from_args() -> Self {
+        let args = Args::parse();
+
+        let replay = args.replay.as_ref().map(|path| {
+            Replay::load(path).unwrap_or_else(|err| {
+                Args::command()
+                    .error(
+                        ErrorKind::Io,
+                        format!("could not read replay '{}': {}", path.display(), err),
+                    )
+                    .exit()
+            })
+        });
+
+        // the editor can start on a level file that doesn't exist yet, it gets created on saving
+        let layout = args
+            .level_file
+            .as_ref()
+            .filter(|path| !args.editor || path.exists())
+            .map(|path| {
+                LevelLayout::load(path, &BrickGrid::default()).unwrap_or_else(|err| {
+                    Args::command()
+                        .error(
+                            ErrorKind::Io,
+                            format!("could not read level file '{}': {}", path.display(), err),
+                        )
+                        .exit()
+                })
+            });
+        // outside the editor the level gets played, so it has to be one that can be
+        if let (Some(layout), false) = (&layout, args.editor) {
+            if matches!(args.mode, GameMode::Endless | GameMode::Versus) {
+                Args::command()
+                    .error(
+                        ErrorKind::ArgumentConflict,
+                        format!(
+                            "--level-file can't be played in {} games, they lay out their own bricks",
+                            args.mode.name()
+                        ),
+                    )
+                    .exit()
+            }
+            // the editor is where a level gets its bricks, a level without any could never be
+            // cleared
+            if !layout.has_breakable_bricks() {
+                Args::command()
+                    .error(
+                        ErrorKind::InvalidValue,
+                        "the --level-file has no bricks that can be broken",
+                    )
+                    .exit()
+            }
+        }
+
+        let image = args.pattern_image.as_ref().map(|path| {
+            LevelGenerator::load_image(path, &BrickGrid::default()).unwrap_or_else(|err| {
+                Args::command()
+                    .error(
+                        ErrorKind::Io,
+                        format!("could not read pattern image '{}': {}", path.display(), err),
+                    )
+                    .exit()
+            })
+        });
+        if args.pattern == Some(Pattern::Image) && image.is_none() {
+            Args::command()
+                .error(
+                    ErrorKind::MissingRequiredArgument,
+                    "the image pattern needs a --pattern-image",
+                )
+                .exit()
+        }
+
+        let mut level_generator = LevelGenerator {
+            pattern: args.pattern,
+            image,
+            ..LevelGenerator::default()
+        };
+        if let Some(text) = &args.pattern_text {
+            level_generator.text = text.clone();
+        }
+
+        LaunchOptions {
+            args,
+            replay,
+            layout,
+            level_generator,
+        }
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        match &self.replay {
+            Some(replay) => Some(replay.seed),
+            None => self.args.seed,
+        }
+    }
+
+    pub fn mode(&self) -> GameMode {
+        match &self.replay {
+            Some(replay) => replay.mode,
+            None => self.args.mode,
+        }
+    }
+
+    pub fn starting_level(&self) -> u32 {
+        match &self.replay {
+            Some(replay) => replay.level,
+            None => self.args.level,
+        }
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        match &self.replay {
+            Some(replay) => replay.difficulty,
+            None => self.args.difficulty,
+        }
+    }
+
+    // Replays bring their own custom difficulty, so they play back the same on any machine
+    pub fn custom_difficulty(&self) -> Option<DifficultyParams> {
+        self.replay.as_ref().map(|replay| replay.custom_difficulty)
+    }
+
+    pub fn speed_up(&self) -> Option<SpeedUpConfig> {
+        self.replay.as_ref().map(|replay| replay.speed_up)
+    }
+
+    pub fn layout(&self) -> Option<LevelLayout> {
+        match &self.replay {
+            Some(replay) => replay.layout.clone(),
+            None => self.layout.clone(),
+        }
+    }
+
+    pub fn net_options(&self) -> NetOptions {
+        let mut net_options = NetOptions {
+            port: self.args.port,
+            input_delay: self.args.input_delay as usize,
+            seed: self.args.seed,
+            ..NetOptions::default()
+        };
+        if let Some(address) = &self.args.join {
+            net_options.address = address.clone();
+        }
+        net_options
+    }
+
+    pub fn autoplayer(&self, skill: BotSkill) -> Autoplayer {
+        Autoplayer {
+            players: [1, 2].map(|player| self.args.bot.contains(&player)),
+            skill: BotSkill {
+                reaction_delay: self.args.bot_reaction.unwrap_or(skill.reaction_delay),
+                error: self.args.bot_error.unwrap_or(skill.error),
+            },
+        }
+    }
+
+    // A network game to start in place of the menu
+    pub fn net_request(&self) -> Option<NetRequest> {
+        let role = if self.args.host {
+            NetRole::Host
+        } else if self.args.join.is_some() {
+            NetRole::Join
+        } else {
+            return None;
+        };
+        Some(NetRequest {
+            role,
+            exit_on_failure: self.args.headless,
+        })
+    }
+
+    // Replays and headless runs have no use for the menu
+    pub fn skip_menu(&self) -> bool {
+        self.args.skip_menu || self.args.headless || self.replay.is_some()
+    }
+}
+
+fn parse_non_negative(value: &str) -> Result<f32, String> {
+    parse_dimension(value, 0.0)
+}
+
+fn parse_width(value: &str) -> Result<f32, String> {
+    parse_dimension(value, MIN_WINDOW_WIDTH)
+}
+
+fn parse_height(value: &str) -> Result<f32, String> {
+    parse_dimension(value, MIN_WINDOW_HEIGHT)
+}
+
+fn parse_dimension(value: &str, min: f32) -> Result<f32, String> {
+    let dimension: f32 = value
+        .parse()
+        .map_err(|_| format!("'{}' is not a number", value))?;
+
+    if !dimension.is_finite() || dimension < min {
+        return Err(format!("must be at least {}", min));
+    }
+
+    Ok(dimension)
+}
@@ -0,0 +1,298 @@
+use bevy::{
+    diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+
+use crate::{
+    game::prelude::{Ball, Brick, Collider, GameConfig, GameRng, Paddle, RallySpeed, Velocity},
+    scaling::ScaledFont,
+    GameState,
+};
+
+const OVERLAY_FONT_SIZE: f32 = 18.0;
+const OVERLAY_COLOR: Color = Color::rgb(0.9, 0.9, 0.3);
+const OUTLINE_COLOR: Color = Color::rgb(0.2, 1.0, 0.2);
+const OUTLINE_THICKNESS: f32 = 2.0;
+// Above everything else in the arena
+const OUTLINE_Z: f32 = 50.0;
+
+// How much each press of a tuning key changes things
+const PADDLE_SPEED_STEP: f32 = 50.0;
+const PADDLE_WIDTH_STEP: f32 = 10.0;
+const MIN_PADDLE_WIDTH: f32 = 20.0;
+const BALL_SPEED_FACTOR: f32 = 1.1;
+
+#[derive(Default)]
+struct DebugOverlay {
+    visible: bool,
+}
+
+#[derive(Component)]
+struct DebugText;
+
+#[derive(Clone, Copy)]
+enum Side {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+// One side of the outline drawn around a collider or ball
+#[derive(Component)]
+struct OutlineEdge {
+    target: Entity,
+    side: Side,
+}
+
+// Marks entities that already have an outline
+#[derive(Component)]
+struct Outlined;
+
+// Frame rate, entity counts, the ball, the state stack and the seed, shown on top of everything
+// and toggled with F3. While it is shown, colliders are outlined and the ball and paddle can be
+// tuned from the keyboard.
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlay>()
+            .add_plugin(FrameTimeDiagnosticsPlugin)
+            .add_startup_system(spawn_overlay)
+            .add_system(toggle_overlay)
+            .add_system(update_overlay.after(toggle_overlay))
+            .add_system(update_outlines.after(toggle_overlay))
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame).with_system(tune_ball_and_paddle),
+            );
+    }
+}
+
+fn toggle_overlay(keyboard_input: Res<Input<KeyCode>>, mut overlay: ResMut<DebugOverlay>) {
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        overlay.visible = !overlay.visible;
+    }
+}
+
+// The overlay outlives every state, whichever UI camera is around draws it
+fn spawn_overlay(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Px(10.0),
+                    left: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            },
+            text: Text::with_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: OVERLAY_FONT_SIZE,
+                    color: OVERLAY_COLOR,
+                },
+                Default::default(),
+            ),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(DebugText)
+        .insert(ScaledFont(OVERLAY_FONT_SIZE));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_overlay(
+    overlay: Res<DebugOverlay>,
+    diagnostics: Res<Diagnostics>,
+    app_state: Res<State<GameState>>,
+    rng: Res<GameRng>,
+    rally_speed: Res<RallySpeed>,
+    game_config: Res<GameConfig>,
+    entity_query: Query<Entity>,
+    brick_query: Query<(), With<Brick>>,
+    ball_query: Query<&Velocity, With<Ball>>,
+    paddle_query: Query<(&Paddle, &Transform)>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<DebugText>>,
+) {
+    for (mut text, mut visibility) in text_query.iter_mut() {
+        if visibility.is_visible != overlay.visible {
+            visibility.is_visible = overlay.visible;
+        }
+        if !overlay.visible {
+            continue;
+        }
+
+        let fps = diagnostics
+            .get(FrameTimeDiagnosticsPlugin::FPS)
+            .and_then(|fps| fps.average())
+            .unwrap_or(0.0);
+        let frame_time = diagnostics
+            .get(FrameTimeDiagnosticsPlugin::FRAME_TIME)
+            .and_then(|frame_time| frame_time.average())
+            .unwrap_or(0.0);
+
+        let mut lines = vec![
+            format!("FPS: {:.0} ({:.1} ms)", fps, frame_time * 1000.0),
+            format!(
+                "Entities: {} ({} bricks)",
+                entity_query.iter().count(),
+                brick_query.iter().count()
+            ),
+        ];
+
+        for velocity in ball_query.iter() {
+            lines.push(format!(
+                "Ball velocity: ({:.0}, {:.0}), speed {:.0}",
+                velocity.x,
+                velocity.y,
+                velocity.length()
+            ));
+        }
+        for (paddle, transform) in paddle_query.iter() {
+            lines.push(format!(
+                "Paddle: speed {:.0}, width {:.0}",
+                paddle.speed, transform.scale.x
+            ));
+        }
+
+        lines.push(format!(
+            "Speed level: {}/{} (x{:.2})",
+            rally_speed.level,
+            game_config.speed_up.max_level,
+            rally_speed.multiplier()
+        ));
+
+        // from the bottom of the stack to the state that is running
+        let states: Vec<_> = app_state
+            .inactives()
+            .iter()
+            .chain([app_state.current()])
+            .map(|state| format!("{:?}", state))
+            .collect();
+        lines.push(format!("States: {}", states.join(" > ")));
+        lines.push(format!("Seed: {}", rng.seed()));
+        lines.push("[ ] paddle speed, - = paddle width, , . ball speed".to_string());
+
+        text.sections[0].value = lines.join("\n");
+    }
+}
+
+// Changes the running game only, the next game starts from the difficulty's values again
+fn tune_ball_and_paddle(
+    overlay: Res<DebugOverlay>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut ball_query: Query<&mut Velocity, With<Ball>>,
+    mut paddle_query: Query<(&mut Paddle, &mut Transform)>,
+) {
+    if !overlay.visible {
+        return;
+    }
+
+    let mut ball_speed_factor = 1.0;
+    if keyboard_input.just_pressed(KeyCode::Period) {
+        ball_speed_factor *= BALL_SPEED_FACTOR;
+    }
+    if keyboard_input.just_pressed(KeyCode::Comma) {
+        ball_speed_factor /= BALL_SPEED_FACTOR;
+    }
+    if ball_speed_factor != 1.0 {
+        for mut velocity in ball_query.iter_mut() {
+            **velocity *= ball_speed_factor;
+        }
+    }
+
+    for (mut paddle, mut transform) in paddle_query.iter_mut() {
+        if keyboard_input.just_pressed(KeyCode::RBracket) {
+            paddle.speed += PADDLE_SPEED_STEP;
+        }
+        if keyboard_input.just_pressed(KeyCode::LBracket) {
+            paddle.speed = (paddle.speed - PADDLE_SPEED_STEP).max(0.0);
+        }
+        if keyboard_input.just_pressed(KeyCode::Equals) {
+            transform.scale.x += PADDLE_WIDTH_STEP;
+        }
+        if keyboard_input.just_pressed(KeyCode::Minus) {
+            transform.scale.x = (transform.scale.x - PADDLE_WIDTH_STEP).max(MIN_PADDLE_WIDTH);
+        }
+    }
+}
+
+// Keeps an outline around every collider and ball while the overlay is shown
+#[allow(clippy::type_complexity)]
+fn update_outlines(
+    mut commands: Commands,
+    overlay: Res<DebugOverlay>,
+    target_query: Query<(Entity, Option<&Outlined>), Or<(With<Collider>, With<Ball>)>>,
+    transform_query: Query<&Transform, Without<OutlineEdge>>,
+    mut edge_query: Query<(Entity, &OutlineEdge, &mut Transform)>,
+) {
+    if !overlay.visible {
+        if overlay.is_changed() {
+            for (edge, ..) in edge_query.iter() {
+                commands.entity(edge).despawn();
+            }
+            for (target, outlined) in target_query.iter() {
+                if outlined.is_some() {
+                    commands.entity(target).remove::<Outlined>();
+                }
+            }
+        }
+        return;
+    }
+
+    for (target, outlined) in target_query.iter() {
+        if outlined.is_some() {
+            continue;
+        }
+        commands.entity(target).insert(Outlined);
+        for side in [Side::Left, Side::Right, Side::Top, Side::Bottom] {
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: OUTLINE_COLOR,
+                        custom_size: Some(Vec2::ONE),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(OutlineEdge { target, side });
+        }
+    }
+
+    for (edge, outline, mut transform) in edge_query.iter_mut() {
+        // the outlined entity is gone, a broken brick or a finished game
+        let target = match transform_query.get(outline.target) {
+            Ok(target) => target,
+            Err(_) => {
+                commands.entity(edge).despawn();
+                continue;
+            }
+        };
+
+        let center = target.translation.truncate();
+        let half_size = target.scale.truncate() / 2.0;
+        let (offset, size) = match outline.side {
+            Side::Left => (
+                Vec2::new(-half_size.x, 0.0),
+                Vec2::new(OUTLINE_THICKNESS, target.scale.y),
+            ),
+            Side::Right => (
+                Vec2::new(half_size.x, 0.0),
+                Vec2::new(OUTLINE_THICKNESS, target.scale.y),
+            ),
+            Side::Top => (
+                Vec2::new(0.0, half_size.y),
+                Vec2::new(target.scale.x, OUTLINE_THICKNESS),
+            ),
+            Side::Bottom => (
+                Vec2::new(0.0, -half_size.y),
+                Vec2::new(target.scale.x, OUTLINE_THICKNESS),
+            ),
+        };
+        transform.translation = (center + offset).extend(OUTLINE_Z);
+        transform.scale = size.extend(1.0);
+    }
+}
@@ -0,0 +1,56 @@
+use bevy::{prelude::*, window::WindowMode};
+
+use crate::settings::{DisplayMode, Settings};
+
+// Switches the window between windowed and fullscreen, either from the settings menu or with
+// Alt+Enter / F11. Layout follows automatically since everything is scaled to the window.
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(toggle_fullscreen)
+            .add_system(apply_display_mode.after(toggle_fullscreen));
+    }
+}
+
+pub fn alt_pressed(keyboard_input: &Input<KeyCode>) -> bool {
+    keyboard_input.any_pressed([KeyCode::LAlt, KeyCode::RAlt])
+}
+
+// Goes by the window rather than the settings, which say windowed when `--fullscreen` overrode
+// them for this run
+fn toggle_fullscreen(
+    keyboard_input: Res<Input<KeyCode>>,
+    windows: Res<Windows>,
+    mut settings: ResMut<Settings>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F11)
+        || (alt_pressed(&keyboard_input) && keyboard_input.just_pressed(KeyCode::Return))
+    {
+        let windowed = windows
+            .get_primary()
+            .map_or(settings.display_mode == DisplayMode::Windowed, |window| {
+                window.mode() == WindowMode::Windowed
+            });
+        // set even when it is already the setting, so the window still follows
+        settings.display_mode = if windowed {
+            DisplayMode::BorderlessFullscreen
+        } else {
+            DisplayMode::Windowed
+        };
+    }
+}
+
+fn apply_display_mode(settings: Res<Settings>, mut windows: ResMut<Windows>) {
+    // the window was already created in the right mode, possibly overridden from the command line
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+
+    if let Some(window) = windows.get_primary_mut() {
+        let mode = settings.display_mode.window_mode();
+        if window.mode() != mode {
+            window.set_mode(mode);
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use bevy::prelude::*;
+
+use crate::{
+    game::prelude::{
+        Ball, Brick, BrickGrid, GameConfig, GameData, GamePlugin, GameRng, Paddle, Velocity,
+        BALL_SPEED, PADDLE_KEYS, X_OFFSET, Y_OFFSET,
+    },
+    headless::SimulationPlugin,
+    scaling::{ScalingPlugin, LOGICAL_HEIGHT, LOGICAL_WIDTH},
+    settings::Settings,
+    state_plugin::{StateChange, StatePlugin},
+    theme::{ThemeDefinition, ThemePlugin, Themes},
+    utilities::MenuEvent,
+    GameState,
+};
+
+// What `EnvConfig` is made of
+pub use crate::game::prelude::{Difficulty, GameMode};
+
+const HALF_WIDTH: f32 = LOGICAL_WIDTH / 2.0 + X_OFFSET;
+const HALF_HEIGHT: f32 = LOGICAL_HEIGHT / 2.0 + Y_OFFSET;
+// Ball, paddle then bricks, one channel each in grid observations
+const GRID_CHANNELS: usize = 3;
+
+// What the agent sees: a flat vector laid out as described by `ObservationKind`
+pub type Observation = Vec<f32>;
+
+// What the agent does with the paddle for the next step
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Action {
+    Stay,
+    Left,
+    Right,
+}
+
+impl Action {
+    pub const ALL: [Action; 3] = [Action::Stay, Action::Left, Action::Right];
+
+    // For agents that pick actions by index, in the order of `ALL`
+    pub fn from_index(index: usize) -> Action {
+        Action::ALL[index % Action::ALL.len()]
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ObservationKind {
+    // Ball x, y, x velocity, y velocity and paddle x, then one value per brick cell counted row
+    // by row from the top, 1 where there is a brick. Positions go from -1 to 1 across the arena,
+    // velocities are in units of the base ball speed. The ball's values are 0 when there is none.
+    Features,
+    // The arena downsampled to `columns` by `rows` cells, row by row from the top, once for the
+    // ball, once for the paddle and once for the bricks. A cell is 1 where something covers it.
+    Grid { columns: usize, rows: usize },
+}
+
+// How the environment plays the game. Only the single player modes can be played, classic,
+// endless and time attack.
+#[derive(Debug, Clone, Copy)]
+pub struct EnvConfig {
+    pub observation: ObservationKind,
+    // frames the game advances on every step, with the action held throughout
+    pub frame_skip: u32,
+    // taken off the reward for every life lost, on top of the score the reward is made of
+    pub life_penalty: f32,
+    // an episode ends after this many steps even if the game isn't over
+    pub max_steps: Option<u32>,
+    pub mode: GameMode,
+    pub difficulty: Difficulty,
+    pub starting_level: u32,
+}
+
+impl Default for EnvConfig {
+    fn default() -> Self {
+        EnvConfig {
+            observation: ObservationKind::Features,
+            frame_skip: 4,
+            life_penalty: 100.0,
+            max_steps: None,
+            mode: GameMode::Classic,
+            difficulty: Difficulty::Normal,
+            starting_level: 1,
+        }
+    }
+}
+
+// A gym-like environment for training agents on the game. It runs the same simulation as
+// headless runs, frame by frame without any waiting, and the agent plays the first paddle.
+//
+//     let mut env = BreakoutEnv::new(EnvConfig::default())?;
+//     let mut observation = env.reset(seed);
+//     loop {
+//         let (next, reward, done) = env.step(agent.act(&observation));
+//         ...
+//     }
+//
+// Every reset starts a new game from scratch, so an episode only depends on its seed and the
+// actions taken.
+pub struct BreakoutEnv {
+    config: EnvConfig,
+    app: Option<App>,
+    steps: u32,
+    score: i32,
+    lives: u32,
+    done: bool,
+}
+
+impl BreakoutEnv {
+    // Fails for configs that can't be played, see `EnvConfig`
+    pub fn new(config: EnvConfig) -> Result<Self, String> {
+        match config.mode {
+            GameMode::Classic | GameMode::Endless | GameMode::TimeAttack => {}
+            mode => return Err(format!("{} needs two players", mode.name())),
+        }
+        if config.starting_level < 1 {
+            return Err("levels start at 1".to_string());
+        }
+        if let ObservationKind::Grid { columns, rows } = config.observation {
+            if columns == 0 || rows == 0 {
+                return Err(format!(
+                    "a {} by {} grid has no cells to observe",
+                    columns, rows
+                ));
+            }
+        }
+
+        Ok(BreakoutEnv {
+            config,
+            app: None,
+            steps: 0,
+            score: 0,
+            lives: 0,
+            done: true,
+        })
+    }
+
+    pub fn config(&self) -> &EnvConfig {
+        &self.config
+    }
+
+    // Length of every observation
+    pub fn observation_size(&self) -> usize {
+        match self.config.observation {
+            ObservationKind::Features => {
+                let grid = BrickGrid::default();
+                5 + grid.n_rows * grid.n_columns
+            }
+            ObservationKind::Grid { columns, rows } => GRID_CHANNELS * columns * rows,
+        }
+    }
+
+    pub fn score(&self) -> i32 {
+        self.score
+    }
+
+    pub fn level(&self) -> u32 {
+        self.app.as_ref().map_or(self.config.starting_level, |app| {
+            app.world.resource::<GameData>().level
+        })
+    }
+
+    // Starts a new game, the levels and every bounce of it follow from the seed
+    pub fn reset(&mut self, seed: u64) -> Observation {
+        let config = &self.config;
+        let mut app = App::new();
+        app.add_plugin(SimulationPlugin)
+            .insert_resource(Themes(vec![ThemeDefinition::fallback()]))
+            .add_event::<StateChange>()
+            .add_event::<MenuEvent>()
+            .insert_resource(GameRng::new(seed))
+            .insert_resource(GameConfig {
+                mode: config.mode,
+                starting_level: config.starting_level,
+                difficulty: config.difficulty,
+                ..default()
+            })
+            // the defaults rather than the player's, so training doesn't depend on who runs it
+            .insert_resource(Settings::default())
+            .add_plugin(ThemePlugin)
+            .add_plugin(GamePlugin)
+            .add_plugin(StatePlugin)
+            .add_plugin(ScalingPlugin)
+            .add_state(GameState::InGame);
+        // sets the game up
+        app.update();
+
+        let game_data = app.world.resource::<GameData>();
+        self.score = game_data.score;
+        self.lives = game_data.lives;
+        self.steps = 0;
+        self.done = false;
+        self.app = Some(app);
+        self.observe()
+    }
+
+    // Plays `frame_skip` frames with the action held. The reward is the score made meanwhile,
+    // less `life_penalty` for every life lost. Once done, the environment needs a reset.
+    pub fn step(&mut self, action: Action) -> (Observation, f32, bool) {
+        assert!(!self.done, "the episode is over, reset the environment");
+        let app = self.app.as_mut().expect("reset before the first step");
+
+        let (left_key, right_key) = PADDLE_KEYS[0];
+        for _ in 0..self.config.frame_skip.max(1) {
+            // held down like a player would, so the keys aren't pressed anew every frame
+            let mut keyboard_input = app.world.resource_mut::<Input<KeyCode>>();
+            for (key, pressed) in [
+                (left_key, action == Action::Left),
+                (right_key, action == Action::Right),
+            ] {
+                if pressed {
+                    keyboard_input.press(key);
+                } else {
+                    keyboard_input.release(key);
+                }
+            }
+
+            app.update();
+            if *app.world.resource::<State<GameState>>().current() == GameState::GameOver {
+                self.done = true;
+                break;
+            }
+        }
+
+        let game_data = app.world.resource::<GameData>();
+        let mut reward = (game_data.score - self.score) as f32;
+        if game_data.lives < self.lives {
+            reward -= (self.lives - game_data.lives) as f32 * self.config.life_penalty;
+        }
+        self.score = game_data.score;
+        self.lives = game_data.lives;
+
+        self.steps += 1;
+        if self
+            .config
+            .max_steps
+            .is_some_and(|max_steps| self.steps >= max_steps)
+        {
+            self.done = true;
+        }
+        (self.observe(), reward, self.done)
+    }
+
+    fn observe(&mut self) -> Observation {
+        let kind = self.config.observation;
+        let mut observation = vec![0.0; self.observation_size()];
+        let world = &mut self.app.as_mut().expect("reset before observing").world;
+
+        match kind {
+            ObservationKind::Features => {
+                let mut ball_query = world.query_filtered::<(&Transform, &Velocity), With<Ball>>();
+                if let Some((transform, velocity)) = ball_query.iter(world).next() {
+                    observation[0] = transform.translation.x / HALF_WIDTH;
+                    observation[1] = transform.translation.y / HALF_HEIGHT;
+                    observation[2] = velocity.x / BALL_SPEED;
+                    observation[3] = velocity.y / BALL_SPEED;
+                }
+
+                let mut paddle_query = world.query::<(&Paddle, &Transform)>();
+                if let Some((_, transform)) = paddle_query
+                    .iter(world)
+                    .find(|(paddle, _)| paddle.player == 0)
+                {
+                    observation[4] = transform.translation.x / HALF_WIDTH;
+                }
+
+                let grid = BrickGrid::default();
+                let mut brick_query = world.query_filtered::<&Transform, With<Brick>>();
+                for transform in brick_query.iter(world) {
+                    if let Some((row, column)) = grid.cell_at(transform.translation.truncate()) {
+                        observation[5 + row * grid.n_columns + column] = 1.0;
+                    }
+                }
+            }
+            ObservationKind::Grid { columns, rows } => {
+                let mut fill = |channel: usize, transform: &Transform| {
+                    let centre = transform.translation.truncate();
+                    let half_size = transform.scale.truncate().abs() / 2.0;
+                    // arena coordinates to cells, with y counted down from the top
+                    let cell = |x: f32, y: f32| {
+                        let column = (x + HALF_WIDTH) / (2.0 * HALF_WIDTH) * columns as f32;
+                        let row = (HALF_HEIGHT - y) / (2.0 * HALF_HEIGHT) * rows as f32;
+                        (
+                            (column.max(0.0) as usize).min(columns - 1),
+                            (row.max(0.0) as usize).min(rows - 1),
+                        )
+                    };
+                    let (left, top) = cell(centre.x - half_size.x, centre.y + half_size.y);
+                    let (right, bottom) = cell(centre.x + half_size.x, centre.y - half_size.y);
+                    for row in top..=bottom {
+                        for column in left..=right {
+                            observation[(channel * rows + row) * columns + column] = 1.0;
+                        }
+                    }
+                };
+
+                let mut ball_query = world.query_filtered::<&Transform, With<Ball>>();
+                ball_query
+                    .iter(world)
+                    .for_each(|transform| fill(0, transform));
+                let mut paddle_query = world.query::<(&Paddle, &Transform)>();
+                for (paddle, transform) in paddle_query.iter(world) {
+                    if paddle.player == 0 {
+                        fill(1, transform);
+                    }
+                }
+                let mut brick_query = world.query_filtered::<&Transform, With<Brick>>();
+                brick_query
+                    .iter(world)
+                    .for_each(|transform| fill(2, transform));
+            }
+        }
+        observation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(config: EnvConfig, seed: u64, steps: usize) -> Vec<(Observation, f32, bool)> {
+        let mut env = BreakoutEnv::new(config).unwrap();
+        let mut results = vec![(env.reset(seed), 0.0, false)];
+        for step in 0..steps {
+            let result = env.step(Action::from_index(step / 10));
+            let done = result.2;
+            results.push(result);
+            if done {
+                break;
+            }
+        }
+        results
+    }
+
+    #[test]
+    fn observations_have_the_promised_size() {
+        let grid = BrickGrid::default();
+        for (observation, size) in [
+            (ObservationKind::Features, 5 + grid.n_rows * grid.n_columns),
+            (
+                ObservationKind::Grid {
+                    columns: 16,
+                    rows: 12,
+                },
+                GRID_CHANNELS * 16 * 12,
+            ),
+        ] {
+            let config = EnvConfig {
+                observation,
+                ..default()
+            };
+            let mut env = BreakoutEnv::new(config).unwrap();
+            assert_eq!(env.observation_size(), size);
+            assert_eq!(env.reset(1).len(), size);
+            assert_eq!(env.step(Action::Left).0.len(), size);
+        }
+    }
+
+    #[test]
+    fn same_seed_and_actions_play_the_same_episode() {
+        let config = EnvConfig {
+            max_steps: Some(150),
+            ..default()
+        };
+        assert_eq!(play(config, 7, 150), play(config, 7, 150));
+    }
+
+    #[test]
+    fn resets_start_over() {
+        let mut env = BreakoutEnv::new(EnvConfig::default()).unwrap();
+        let first = env.reset(3);
+        for _ in 0..50 {
+            if env.step(Action::Right).2 {
+                break;
+            }
+        }
+        assert_eq!(env.reset(3), first);
+    }
+
+    #[test]
+    fn two_player_modes_are_rejected() {
+        for mode in [GameMode::Versus, GameMode::Coop] {
+            let config = EnvConfig { mode, ..default() };
+            assert!(BreakoutEnv::new(config).is_err());
+        }
+    }
+
+    #[test]
+    fn empty_grids_are_rejected() {
+        for (columns, rows) in [(0, 4), (4, 0)] {
+            let config = EnvConfig {
+                observation: ObservationKind::Grid { columns, rows },
+                ..default()
+            };
+            assert!(BreakoutEnv::new(config).is_err());
+        }
+    }
+
+    #[test]
+    fn level_zero_is_rejected() {
+        let config = EnvConfig {
+            starting_level: 0,
+            ..default()
+        };
+        assert!(BreakoutEnv::new(config).is_err());
+    }
+}
@@ -0,0 +1,468 @@
+use bevy::{
+    input::{keyboard::KeyboardInput, mouse::MouseButtonInput, InputSystem},
+    prelude::*,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    netcode::NetSession,
+    replay::{PlayBackInputs, ReplayPlayer},
+    scaling::{ScaledFont, LOGICAL_HEIGHT, LOGICAL_WIDTH},
+    settings::Settings,
+    state_plugin::StateChange,
+    utilities::{despawn_entities, TEXT_COLOR},
+    GameState,
+};
+
+use super::{
+    ball::{Ball, Velocity, BALL_SIZE},
+    clock::GameClock,
+    components::GameConfig,
+    difficulty::Difficulty,
+    game_mode::GameMode,
+    paddle::{Paddle, PADDLE_KEYS},
+    rng::GameRng,
+    walls::{X_OFFSET, Y_OFFSET},
+};
+
+// Time on the main menu without any input before the demo starts
+const DEMO_DELAY: f32 = 20.0;
+const DEMO_FONT_SIZE: f32 = 24.0;
+const DEMO_MARGIN: f32 = 8.0;
+
+// How well a bot plays
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BotSkill {
+    // seconds between the ball changing direction and the bot going after it
+    pub reaction_delay: f32,
+    // the most the bot misjudges where the ball will be, in pixels either way
+    pub error: f32,
+}
+
+impl Default for BotSkill {
+    fn default() -> Self {
+        BotSkill {
+            reaction_delay: 0.2,
+            error: 25.0,
+        }
+    }
+}
+
+// Which players bots play for, from the command line. The settings can add one for the second
+// player, and the main menu's demo one for the first.
+#[derive(Default)]
+pub struct Autoplayer {
+    pub players: [bool; 2],
+    pub skill: BotSkill,
+}
+
+#[derive(Default, Clone, Copy)]
+struct Bot {
+    // where the paddle is headed
+    target: Option<f32>,
+    // where the ball is headed now, and how long until the bot reacts to it
+    next_target: Option<(f32, f32)>,
+    last_velocity: Vec2,
+}
+
+// The bots of the current game
+pub struct Bots {
+    active: [bool; 2],
+    bots: [Bot; 2],
+    // kept apart from `GameRng`, so bots don't change the game a replay of it plays back
+    rng: StdRng,
+}
+
+impl Default for Bots {
+    fn default() -> Self {
+        Bots {
+            active: [false; 2],
+            bots: default(),
+            rng: StdRng::seed_from_u64(0),
+        }
+    }
+}
+
+impl Bots {
+    // Games played by bots don't count for high scores
+    pub fn any_playing(&self) -> bool {
+        self.active.contains(&true)
+    }
+}
+
+// The demo played on the main menu after a while without input
+#[derive(Default)]
+struct Demo {
+    idle: f32,
+    // the menu's set up, to go back to after the demo
+    saved_config: Option<GameConfig>,
+}
+
+#[derive(Component)]
+struct DemoText;
+
+// Bots that play a paddle by working out where the ball will come down, bounces off the side
+// walls included, and pressing its keys to get there
+pub struct AutoplayerPlugin;
+
+impl Plugin for AutoplayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Autoplayer>()
+            .init_resource::<Bots>()
+            .init_resource::<Demo>()
+            // bots don't play replays, but which keys end up held shouldn't be up to the threads
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                drive_bots.after(InputSystem).after(PlayBackInputs),
+            )
+            .add_system_to_stage(CoreStage::PreUpdate, stop_demo_on_input.after(InputSystem))
+            .add_system_set(
+                SystemSet::on_enter(GameState::InGame)
+                    .with_system(start_bots)
+                    .with_system(render_demo_text),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::InGame)
+                    .with_system(release_keys)
+                    .with_system(despawn_entities::<DemoText>),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(reset_idle_time))
+            .add_system_set(
+                SystemSet::on_update(GameState::MainMenu).with_system(start_demo_when_idle),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(stop_demo));
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn start_bots(
+    autoplayer: Res<Autoplayer>,
+    settings: Res<Settings>,
+    game_config: Res<GameConfig>,
+    demo: Res<Demo>,
+    rng: Res<GameRng>,
+    session: Option<Res<NetSession>>,
+    replay_player: Option<Res<ReplayPlayer>>,
+    mut bots: ResMut<Bots>,
+) {
+    // network games are between the two people playing them
+    let second_player = settings.second_player_bot && session.is_none();
+    let mut active = autoplayer.players;
+    active[0] |= demo.saved_config.is_some();
+    active[1] |= second_player;
+    for (player, active) in active.iter_mut().enumerate() {
+        // a replay already has the keys any bot pressed in it
+        *active &= player < game_config.mode.players() && replay_player.is_none();
+    }
+
+    *bots = Bots {
+        active,
+        bots: default(),
+        rng: StdRng::seed_from_u64(rng.seed()),
+    };
+}
+
+fn drive_bots(
+    app_state: Res<State<GameState>>,
+    autoplayer: Res<Autoplayer>,
+    clock: Res<GameClock>,
+    mut bots: ResMut<Bots>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    ball_query: Query<(&Transform, &Velocity), With<Ball>>,
+    paddle_query: Query<(&Paddle, &Transform)>,
+) {
+    if *app_state.current() != GameState::InGame || !bots.any_playing() {
+        return;
+    }
+    let (ball_transform, velocity) = match ball_query.get_single() {
+        Ok(ball) => ball,
+        Err(_) => return,
+    };
+    let ball_position = ball_transform.translation.truncate();
+    let skill = autoplayer.skill;
+
+    let bots = &mut *bots;
+    for (paddle, paddle_transform) in paddle_query.iter() {
+        if !bots.active[paddle.player] {
+            continue;
+        }
+        let bot = &mut bots.bots[paddle.player];
+        let paddle_position = paddle_transform.translation.truncate();
+
+        // a serve or a bounce, somewhere new to go
+        let changed = bot.last_velocity == Vec2::ZERO
+            || velocity.normalize().dot(bot.last_velocity.normalize()) < 0.999;
+        bot.last_velocity = **velocity;
+        if changed {
+            let error = if skill.error > 0.0 {
+                bots.rng.gen_range(-skill.error..skill.error)
+            } else {
+                0.0
+            };
+            let x = predict_intercept(ball_position, **velocity, paddle_position.y);
+            bot.next_target = Some((x + error, skill.reaction_delay));
+        }
+
+        if let Some((target, delay)) = bot.next_target {
+            let delay = delay - clock.delta_seconds();
+            if delay <= 0.0 {
+                bot.target = Some(target);
+                bot.next_target = None;
+            } else {
+                bot.next_target = Some((target, delay));
+            }
+        }
+
+        // close enough once another frame of movement would overshoot
+        let tolerance = (paddle.speed * clock.delta_seconds()).max(1.0);
+        let offset = bot.target.unwrap_or(paddle_position.x) - paddle_position.x;
+        let (left_key, right_key) = PADDLE_KEYS[paddle.player];
+        for (key, pressed) in [
+            (left_key, offset < -tolerance),
+            (right_key, offset > tolerance),
+        ] {
+            if pressed {
+                keyboard_input.press(key);
+            } else {
+                keyboard_input.release(key);
+            }
+        }
+    }
+}
+
+// Where the ball will be across when it gets down (or up) to a paddle at `paddle_y`. A ball
+// heading away is expected back off the far end of the arena.
+fn predict_intercept(position: Vec2, velocity: Vec2, paddle_y: f32) -> f32 {
+    if velocity.y == 0.0 {
+        return position.x;
+    }
+
+    let half_width = LOGICAL_WIDTH / 2.0 + X_OFFSET - BALL_SIZE.x / 2.0;
+    let half_height = LOGICAL_HEIGHT / 2.0 + Y_OFFSET - BALL_SIZE.y / 2.0;
+    // the ball's centre meets the paddle half a ball short of it, on the side it comes from
+    let facing = if paddle_y > 0.0 { -1.0 } else { 1.0 };
+    let meet_y = paddle_y + facing * BALL_SIZE.y / 2.0;
+
+    let heading_toward = (meet_y - position.y) * velocity.y > 0.0;
+    let distance = if heading_toward {
+        (meet_y - position.y).abs()
+    } else {
+        // the end of the arena across from the paddle
+        let far_y = facing * half_height;
+        (far_y - position.y).abs() + (far_y - meet_y).abs()
+    };
+    let x = position.x + velocity.x * distance / velocity.y.abs();
+
+    // every bounce off a side wall mirrors the rest of the way
+    let width = half_width * 2.0;
+    let folded = (x + half_width).rem_euclid(width * 2.0);
+    let folded = if folded > width {
+        width * 2.0 - folded
+    } else {
+        folded
+    };
+    folded - half_width
+}
+
+fn release_keys(bots: Res<Bots>, mut keyboard_input: ResMut<Input<KeyCode>>) {
+    for (player, (left_key, right_key)) in PADDLE_KEYS.into_iter().enumerate() {
+        if bots.active[player] {
+            keyboard_input.release(left_key);
+            keyboard_input.release(right_key);
+        }
+    }
+}
+
+fn reset_idle_time(mut demo: ResMut<Demo>) {
+    demo.idle = 0.0;
+}
+
+fn start_demo_when_idle(
+    time: Res<Time>,
+    mut demo: ResMut<Demo>,
+    mut game_config: ResMut<GameConfig>,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut mouse_events: EventReader<MouseButtonInput>,
+    mut event_state_change: EventWriter<StateChange>,
+) {
+    if keyboard_events.iter().count() > 0 || mouse_events.iter().count() > 0 {
+        demo.idle = 0.0;
+        return;
+    }
+
+    demo.idle += time.delta_seconds();
+    if demo.idle < DEMO_DELAY {
+        return;
+    }
+
+    // a classic game, whatever is picked in the menu
+    let demo_config = GameConfig {
+        mode: GameMode::Classic,
+        starting_level: 1,
+        difficulty: Difficulty::Normal,
+        custom_difficulty: game_config.custom_difficulty,
+        layout: None,
+        speed_up: game_config.speed_up,
+    };
+    demo.saved_config = Some(std::mem::replace(&mut *game_config, demo_config));
+    event_state_change.send(StateChange::Set(GameState::InGame));
+}
+
+// Any key or click ends the demo. The input goes no further, so it doesn't also pause the game.
+fn stop_demo_on_input(
+    mut demo: ResMut<Demo>,
+    mut game_config: ResMut<GameConfig>,
+    mut app_state: ResMut<State<GameState>>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut mouse_events: EventReader<MouseButtonInput>,
+) {
+    let input = keyboard_events.iter().count() > 0 || mouse_events.iter().count() > 0;
+    if !input || demo.saved_config.is_none() || *app_state.current() != GameState::InGame {
+        return;
+    }
+
+    keyboard_input.clear();
+    if let Some(saved_config) = demo.saved_config.take() {
+        *game_config = saved_config;
+    }
+    let _ = app_state.set(GameState::MainMenu);
+}
+
+// The demo goes straight back to the menu when the bot loses
+fn stop_demo(
+    mut demo: ResMut<Demo>,
+    mut game_config: ResMut<GameConfig>,
+    mut event_state_change: EventWriter<StateChange>,
+) {
+    if let Some(saved_config) = demo.saved_config.take() {
+        *game_config = saved_config;
+        event_state_change.send(StateChange::Set(GameState::MainMenu));
+    }
+}
+
+fn render_demo_text(mut commands: Commands, asset_server: Res<AssetServer>, demo: Res<Demo>) {
+    if demo.saved_config.is_none() {
+        return;
+    }
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    top: Val::Px(DEMO_MARGIN),
+                    left: Val::Px(0.0),
+                    ..default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(DemoText)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text::with_section(
+                        "Demo - press any key",
+                        TextStyle {
+                            font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                            font_size: DEMO_FONT_SIZE,
+                            color: TEXT_COLOR,
+                        },
+                        default(),
+                    ),
+                    ..default()
+                })
+                .insert(ScaledFont(DEMO_FONT_SIZE));
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PADDLE_Y: f32 = -200.0;
+
+    // how far the ball's centre gets from the middle of the arena
+    fn half_width() -> f32 {
+        LOGICAL_WIDTH / 2.0 + X_OFFSET - BALL_SIZE.x / 2.0
+    }
+
+    fn half_height() -> f32 {
+        LOGICAL_HEIGHT / 2.0 + Y_OFFSET - BALL_SIZE.y / 2.0
+    }
+
+    // where the bottom paddle is met by the ball's centre
+    fn meet_y() -> f32 {
+        PADDLE_Y + BALL_SIZE.y / 2.0
+    }
+
+    fn assert_near(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "expected {}, got {}",
+            expected,
+            actual
+        );
+    }
+
+    #[test]
+    fn ball_going_straight_down_lands_where_it_is() {
+        let x = predict_intercept(Vec2::new(37.0, 100.0), Vec2::new(0.0, -300.0), PADDLE_Y);
+        assert_near(x, 37.0);
+    }
+
+    #[test]
+    fn ball_moving_sideways_only_stays_put() {
+        let x = predict_intercept(Vec2::new(-12.0, 50.0), Vec2::new(300.0, 0.0), PADDLE_Y);
+        assert_near(x, -12.0);
+    }
+
+    #[test]
+    fn diagonal_ball_lands_along_its_path() {
+        let start = Vec2::new(0.0, meet_y() + 100.0);
+        let x = predict_intercept(start, Vec2::new(100.0, -200.0), PADDLE_Y);
+        assert_near(x, 50.0);
+    }
+
+    #[test]
+    fn side_walls_mirror_the_path() {
+        // heading 100 past the right wall, so it comes back 100 short of it
+        let start = Vec2::new(half_width() - 50.0, meet_y() + 150.0);
+        let x = predict_intercept(start, Vec2::new(300.0, -300.0), PADDLE_Y);
+        assert_near(x, half_width() - 100.0);
+    }
+
+    #[test]
+    fn ball_heading_away_comes_back_off_the_far_end() {
+        let start = Vec2::new(0.0, 0.0);
+        let x = predict_intercept(start, Vec2::new(10.0, 300.0), PADDLE_Y);
+        let distance = half_height() + (half_height() - meet_y());
+        assert_near(x, distance * 10.0 / 300.0);
+    }
+
+    #[test]
+    fn top_paddle_is_met_from_below() {
+        let paddle_y = 200.0;
+        let meet_y = paddle_y - BALL_SIZE.y / 2.0;
+        let start = Vec2::new(0.0, meet_y - 100.0);
+        let x = predict_intercept(start, Vec2::new(-100.0, 100.0), paddle_y);
+        assert_near(x, -100.0);
+    }
+
+    #[test]
+    fn prediction_stays_in_the_arena() {
+        for step in 0..50 {
+            let angle = step as f32 * 0.3;
+            let velocity = Vec2::new(angle.cos(), angle.sin()) * 400.0;
+            let x = predict_intercept(Vec2::new(10.0, 30.0), velocity, PADDLE_Y);
+            assert!(x.abs() <= half_width() + 1e-3, "{} out of the arena", x);
+        }
+    }
+}
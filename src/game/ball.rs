@@ -1,25 +1,35 @@
 use bevy::{
+    ecs::system::SystemParam,
     math::const_vec3,
     prelude::*,
     sprite::collide_aabb::{collide, Collision},
 };
-use rand::{thread_rng, Rng};
+use rand::Rng;
 
-use crate::GameState;
+use crate::{theme::Theme, GameState};
 
-use super::components::{Brick, Collider, GameData, GameEntity, FailZone};
+use super::{
+    clock::GameClock,
+    components::{Brick, Collider, FailZone, GameConfig, GameData, GameEntity},
+    difficulty::DifficultyParams,
+    events::{
+        BallHitPaddle, BallHitWall, BallLost, BallReset, BrickDestroyed, BrickHit, CollisionCheck,
+        LevelCleared,
+    },
+    paddle::{OneWay, Paddle},
+    rng::GameRng,
+};
 
 // We set the z-value of the ball to 1 so it renders on top in the case of overlapping sprites.
 const BALL_STARTING_POSITION: Vec3 = const_vec3!([0.0, -150.0, 1.0]);
-const BALL_SIZE: Vec3 = const_vec3!([30.0, 30.0, 0.0]);
-const BALL_SPEED: f32 = 165.0;
-const BALL_COLOR: Color = Color::rgb(1.0, 0.5, 0.5);
+pub const BALL_SIZE: Vec3 = const_vec3!([30.0, 30.0, 0.0]);
+pub const BALL_SPEED: f32 = 165.0;
 
 #[derive(Component)]
-struct Ball;
+pub struct Ball;
 
 #[derive(Component, Deref, DerefMut)]
-struct Velocity(Vec2);
+pub struct Velocity(Vec2);
 
 pub struct BallPlugin;
 
@@ -28,55 +38,136 @@ impl Plugin for BallPlugin {
         app.add_system_set(SystemSet::on_enter(GameState::InGame).with_system(render_ball))
             .add_system_set(
                 SystemSet::on_update(GameState::InGame)
-                    .with_system(check_for_collisions)
-                    .with_system(apply_velocity.before(check_for_collisions)),
+                    .with_system(check_for_collisions.label(CollisionCheck))
+                    .with_system(apply_velocity.before(CollisionCheck))
+                    .with_system(reset_ball.label(BallReset).after(CollisionCheck)),
             );
     }
 }
 
-fn render_ball(mut commands: Commands) {
-    // randomize initial ball direction
-    let mut rng = thread_rng();
+// the ball gets a little faster on every level
+fn ball_speed(params: &DifficultyParams, level: u32) -> f32 {
+    BALL_SPEED * params.ball_speed * (1.0 + params.ball_acceleration * (level - 1) as f32)
+}
+
+// randomize initial ball direction
+fn initial_velocity(rng: &mut GameRng, speed: f32) -> Velocity {
     let initial_direction = Vec2::new(rng.gen_range(-0.6..0.6), rng.gen_range(-0.7..-0.1));
+    Velocity(initial_direction.normalize() * speed)
+}
+
+fn render_ball(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    game_config: Res<GameConfig>,
+    theme: Res<Theme>,
+) {
+    let speed = ball_speed(&game_config.difficulty_params(), game_config.starting_level);
     // Ball
     commands
         .spawn()
         .insert(Ball)
         .insert(GameEntity)
-        .insert_bundle(SpriteBundle {
-            transform: Transform {
-                translation: BALL_STARTING_POSITION,
-                scale: BALL_SIZE,
-                ..default()
-            },
-            sprite: Sprite {
-                color: BALL_COLOR,
-                ..default()
-            },
+        .insert_bundle(theme.ball.sprite_bundle(Transform {
+            translation: BALL_STARTING_POSITION,
+            scale: BALL_SIZE,
             ..default()
-        })
-        .insert(Velocity(initial_direction.normalize() * BALL_SPEED));
+        }))
+        .insert(initial_velocity(&mut rng, speed));
+}
+
+// The ball is served again from the start for every new level, and after losing a life. It is
+// served toward the side it was lost on, which is the top for the second player in versus.
+fn reset_ball(
+    mut levels_cleared: EventReader<LevelCleared>,
+    mut balls_lost: EventReader<BallLost>,
+    mut rng: ResMut<GameRng>,
+    game_config: Res<GameConfig>,
+    game_data: Res<GameData>,
+    mut ball_query: Query<(&mut Transform, &mut Velocity), With<Ball>>,
+) {
+    let ball_lost = balls_lost.iter().last();
+    let level = match levels_cleared.iter().last() {
+        Some(level_cleared) => level_cleared.level + 1,
+        None if ball_lost.is_some() => game_data.level,
+        None => return,
+    };
+    let towards_top = ball_lost.is_some_and(|ball_lost| ball_lost.position.y > 0.0);
+
+    let speed = ball_speed(&game_config.difficulty_params(), level);
+    for (mut transform, mut velocity) in ball_query.iter_mut() {
+        transform.translation = BALL_STARTING_POSITION;
+        *velocity = initial_velocity(&mut rng, speed);
+        if towards_top {
+            transform.translation.y = -transform.translation.y;
+            velocity.y = -velocity.y;
+        }
+    }
 }
 
-fn apply_velocity(time: Res<Time>, mut query: Query<(&mut Transform, &Velocity)>) {
+fn apply_velocity(clock: Res<GameClock>, mut query: Query<(&mut Transform, &Velocity)>) {
     for (mut transform, velocity) in query.iter_mut() {
-        transform.translation.x += velocity.x * time.delta_seconds();
-        transform.translation.y += velocity.y * time.delta_seconds();
+        transform.translation.x += velocity.x * clock.delta_seconds();
+        transform.translation.y += velocity.y * clock.delta_seconds();
     }
 }
 
+#[derive(SystemParam)]
+struct CollisionEvents<'w, 's> {
+    paddle_hits: EventWriter<'w, 's, BallHitPaddle>,
+    wall_hits: EventWriter<'w, 's, BallHitWall>,
+    brick_hits: EventWriter<'w, 's, BrickHit>,
+    bricks_destroyed: EventWriter<'w, 's, BrickDestroyed>,
+    balls_lost: EventWriter<'w, 's, BallLost>,
+    levels_cleared: EventWriter<'w, 's, LevelCleared>,
+}
+
+// Moves the ball off whatever it hits and reports what happened through events. Bricks are
+// damaged here, but scoring and state changes are left to the systems reading the events.
+#[allow(clippy::type_complexity)]
 fn check_for_collisions(
     mut commands: Commands,
-    mut game_data: ResMut<GameData>,
+    clock: Res<GameClock>,
+    game_data: Res<GameData>,
     mut ball_query: Query<(&mut Velocity, &Transform), With<Ball>>,
-    collider_query: Query<(Entity, &Transform, Option<&Brick>, Option<&FailZone>), With<Collider>>,
-    mut app_state: ResMut<State<GameState>>,
+    mut collider_query: Query<
+        (
+            Entity,
+            &Transform,
+            Option<&mut Brick>,
+            Option<&FailZone>,
+            Option<&Paddle>,
+            Option<&OneWay>,
+        ),
+        With<Collider>,
+    >,
+    mut events: CollisionEvents,
 ) {
+    // nothing moved while the game stands still, and a ball still touching something would hit
+    // it again
+    if clock.delta_seconds() == 0.0 {
+        return;
+    }
+
     let (mut ball_velocity, ball_transform) = ball_query.single_mut();
     let ball_size = ball_transform.scale.truncate();
+    let mut bricks_left = collider_query
+        .iter()
+        .filter(|(_, _, maybe_brick, ..)| {
+            maybe_brick
+                .as_ref()
+                .is_some_and(|brick| brick.kind.is_breakable())
+        })
+        .count();
 
     // check collision with walls
-    for (collider_entity, transform, maybe_brick, maybe_fail_zone) in collider_query.iter() {
+    for (collider_entity, transform, maybe_brick, maybe_fail_zone, maybe_paddle, maybe_one_way) in
+        collider_query.iter_mut()
+    {
+        if maybe_one_way.is_some() && ball_velocity.y > 0.0 {
+            continue;
+        }
+
         let collision = collide(
             ball_transform.translation,
             ball_size,
@@ -84,39 +175,83 @@ fn check_for_collisions(
             transform.scale.truncate(),
         );
 
-        if let Some(collision) = collision {
-            if maybe_fail_zone.is_some() {
-                app_state.set(GameState::GameOver).unwrap();
-            } else {
-                // Bricks should be despawned and increment the scoreboard on collision
-                if maybe_brick.is_some() {
-                    game_data.score += 1;
-                    commands.entity(collider_entity).despawn();
-                }
-    
-                // reflect the ball when it collides
-                let mut reflect_x = false;
-                let mut reflect_y = false;
-    
-                // only reflect if the ball's velocity is going in the opposite direction of the
-                // collision
-                match collision {
-                    Collision::Left => reflect_x = ball_velocity.x > 0.0,
-                    Collision::Right => reflect_x = ball_velocity.x < 0.0,
-                    Collision::Top => reflect_y = ball_velocity.y < 0.0,
-                    Collision::Bottom => reflect_y = ball_velocity.y > 0.0,
-                    Collision::Inside => { /* do nothing */ }
-                }
-    
-                // reflect velocity on the x-axis if we hit something on the x-axis
-                if reflect_x {
-                    ball_velocity.x = -ball_velocity.x;
-                }
-    
-                // reflect velocity on the y-axis if we hit something on the y-axis
-                if reflect_y {
-                    ball_velocity.y = -ball_velocity.y;
+        let collision = match collision {
+            Some(collision) => collision,
+            None => continue,
+        };
+
+        if let Some(fail_zone) = maybe_fail_zone {
+            events.balls_lost.send(BallLost {
+                position: ball_transform.translation.truncate(),
+                player: fail_zone.player,
+            });
+            continue;
+        }
+
+        let mut brick_destroyed = false;
+        let mut brick_hit = None;
+        if let Some(mut brick) = maybe_brick {
+            if brick.kind.is_breakable() {
+                brick.hits_left -= 1;
+            }
+
+            // Bricks are despawned once they run out of hits
+            if brick.hits_left == 0 {
+                brick_destroyed = true;
+                commands.entity(collider_entity).despawn_recursive();
+                events.bricks_destroyed.send(BrickDestroyed {
+                    position: transform.translation.truncate(),
+                    kind: brick.kind,
+                    row: brick.row,
+                    points: brick.kind.points(),
+                });
+
+                bricks_left -= 1;
+                if bricks_left == 0 {
+                    events.levels_cleared.send(LevelCleared {
+                        level: game_data.level,
+                    });
                 }
+            } else {
+                brick_hit = Some(brick.kind);
+            }
+        }
+
+        // reflect the ball when it collides
+        let mut reflect_x = false;
+        let mut reflect_y = false;
+
+        // only reflect if the ball's velocity is going in the opposite direction of the
+        // collision
+        match collision {
+            Collision::Left => reflect_x = ball_velocity.x > 0.0,
+            Collision::Right => reflect_x = ball_velocity.x < 0.0,
+            Collision::Top => reflect_y = ball_velocity.y < 0.0,
+            Collision::Bottom => reflect_y = ball_velocity.y > 0.0,
+            Collision::Inside => { /* do nothing */ }
+        }
+
+        // reflect velocity on the x-axis if we hit something on the x-axis
+        if reflect_x {
+            ball_velocity.x = -ball_velocity.x;
+        }
+
+        // reflect velocity on the y-axis if we hit something on the y-axis
+        if reflect_y {
+            ball_velocity.y = -ball_velocity.y;
+        }
+
+        if (reflect_x || reflect_y) && !brick_destroyed {
+            let position = ball_transform.translation.truncate();
+            if let Some(paddle) = maybe_paddle {
+                events.paddle_hits.send(BallHitPaddle {
+                    position,
+                    player: paddle.player,
+                });
+            } else if let Some(kind) = brick_hit {
+                events.brick_hits.send(BrickHit { position, kind });
+            } else {
+                events.wall_hits.send(BallHitWall { position });
             }
         }
     }
@@ -0,0 +1,140 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::GameState;
+
+use super::{
+    ball::{Ball, Velocity, BALL_SIZE, BALL_SPEED},
+    clock::GameClock,
+    components::GameEntity,
+    events::CollisionCheck,
+};
+
+// Longest the trail can get, in frames of history
+const MAX_TRAIL_LENGTH: usize = 12;
+// Number of trail sprites shown at the ball's starting speed, it grows (up to the max) as the
+// ball gets faster
+const BASE_TRAIL_LENGTH: f32 = 5.0;
+const TRAIL_ALPHA: f32 = 0.5;
+// Jumps longer than this are the ball being put back in place, not movement to draw a trail for
+const TELEPORT_DISTANCE: f32 = 100.0;
+// Just under the ball
+const TRAIL_Z: f32 = 0.9;
+
+// Recent positions of a ball, most recent first
+#[derive(Component, Default)]
+struct BallTrail {
+    positions: VecDeque<Vec2>,
+}
+
+#[derive(Component)]
+struct TrailSegment {
+    ball: Entity,
+    index: usize,
+}
+
+// Draws a fading trail behind each ball. The trail takes the ball's current look, so anything
+// that tints the ball (like a power-up) tints its trail too.
+pub struct BallTrailPlugin;
+
+impl Plugin for BallTrailPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(add_trails)
+                .with_system(record_positions.after(CollisionCheck))
+                .with_system(draw_trails.after(record_positions)),
+        );
+    }
+}
+
+fn add_trails(mut commands: Commands, ball_query: Query<Entity, (With<Ball>, Without<BallTrail>)>) {
+    for ball in ball_query.iter() {
+        commands.entity(ball).insert(BallTrail::default());
+        for index in 0..MAX_TRAIL_LENGTH {
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::ONE),
+                        ..default()
+                    },
+                    visibility: Visibility { is_visible: false },
+                    ..default()
+                })
+                .insert(TrailSegment { ball, index })
+                .insert(GameEntity);
+        }
+    }
+}
+
+fn record_positions(clock: Res<GameClock>, mut ball_query: Query<(&Transform, &mut BallTrail)>) {
+    // nothing moved, e.g. during a hit-stop
+    if clock.delta_seconds() <= 0.0 {
+        return;
+    }
+
+    for (transform, mut trail) in ball_query.iter_mut() {
+        let position = transform.translation.truncate();
+        if let Some(last) = trail.positions.front() {
+            if last.distance(position) > TELEPORT_DISTANCE {
+                trail.positions.clear();
+            }
+        }
+
+        trail.positions.push_front(position);
+        trail.positions.truncate(MAX_TRAIL_LENGTH + 1);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn draw_trails(
+    mut commands: Commands,
+    ball_query: Query<(&BallTrail, &Velocity, &Sprite, &Handle<Image>), With<Ball>>,
+    mut segment_query: Query<
+        (
+            Entity,
+            &TrailSegment,
+            &mut Transform,
+            &mut Sprite,
+            &mut Handle<Image>,
+            &mut Visibility,
+        ),
+        Without<Ball>,
+    >,
+) {
+    for (entity, segment, mut transform, mut sprite, mut texture, mut visibility) in
+        segment_query.iter_mut()
+    {
+        let (trail, velocity, ball_sprite, ball_texture) = match ball_query.get(segment.ball) {
+            Ok(ball) => ball,
+            Err(_) => {
+                // the ball is gone
+                commands.entity(entity).despawn();
+                continue;
+            }
+        };
+
+        let length = ((velocity.length() / BALL_SPEED * BASE_TRAIL_LENGTH).round() as usize)
+            .clamp(1, MAX_TRAIL_LENGTH);
+        // the newest position is where the ball is now, so the trail starts one behind it
+        let position = match trail.positions.get(segment.index + 1) {
+            Some(position) if segment.index < length => *position,
+            _ => {
+                visibility.is_visible = false;
+                continue;
+            }
+        };
+
+        // segments shrink and fade towards the end of the trail
+        let remaining = 1.0 - (segment.index + 1) as f32 / (length + 1) as f32;
+        visibility.is_visible = true;
+        transform.translation = position.extend(TRAIL_Z);
+        transform.scale = BALL_SIZE * remaining;
+        *texture = ball_texture.clone();
+        sprite.color = ball_sprite.color;
+        sprite
+            .color
+            .set_a(ball_sprite.color.a() * TRAIL_ALPHA * remaining);
+    }
+}
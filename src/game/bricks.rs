@@ -1,13 +1,21 @@
 use bevy::{math::const_vec2, prelude::*};
 
 use crate::{
-    game::components::{Brick, Collider},
+    game::{
+        components::{Brick, Collider, GameConfig, GameEntity},
+        game_mode::GameMode,
+    },
+    scaling::{LOGICAL_HEIGHT, LOGICAL_WIDTH},
+    theme::Theme,
     GameState,
 };
 
-use super::walls::{X_OFFSET, Y_OFFSET};
+use super::{
+    events::{CollisionCheck, LevelCleared},
+    level_layout::LevelLayout,
+    walls::{X_OFFSET, Y_OFFSET},
+};
 
-const BRICK_COLOR: Color = Color::rgb(0.6, 0.5, 0.4);
 const BRICK_SIZE: Vec2 = const_vec2!([100., 30.]);
 const GAP_BETWEEN_PADDLE_AND_BRICKS: f32 = 300.0;
 const GAP_BETWEEN_BRICKS: f32 = 5.0;
@@ -15,71 +23,245 @@ const GAP_BETWEEN_BRICKS: f32 = 5.0;
 const GAP_BETWEEN_BRICKS_AND_SIDES: f32 = 20.0;
 const GAP_BETWEEN_BRICKS_AND_CEILING: f32 = 20.0;
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum BrickKind {
+    Normal,
+    // takes two hits to break
+    Strong,
+    // can't be broken, and doesn't need to be for the level to be cleared
+    Steel,
+    // breaks like a normal brick, and adds time to the clock in time attack
+    Time,
+}
+
+impl BrickKind {
+    pub fn hits(&self) -> u32 {
+        match self {
+            BrickKind::Normal | BrickKind::Time => 1,
+            BrickKind::Strong => 2,
+            BrickKind::Steel => u32::MAX,
+        }
+    }
+
+    pub fn points(&self) -> i32 {
+        match self {
+            BrickKind::Normal | BrickKind::Time => 10,
+            BrickKind::Strong => 30,
+            BrickKind::Steel => 0,
+        }
+    }
+
+    pub fn is_breakable(&self) -> bool {
+        *self != BrickKind::Steel
+    }
+}
+
 pub struct BricksPlugin;
 
 impl Plugin for BricksPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_enter(GameState::InGame).with_system(render_bricks));
+        app.add_system_set(SystemSet::on_enter(GameState::InGame).with_system(render_bricks))
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(render_next_level_bricks)
+                    .with_system(update_brick_looks.after(CollisionCheck)),
+            );
     }
 }
 
-fn render_bricks(mut commands: Commands, windows: Res<Windows>) {
-    let window = windows.get_primary().unwrap();
-    let window_height = window.height();
-    let window_width = window.width();
-
-    let arena_width = (window_width / 2.0 + X_OFFSET) * 2.0; // + WALL_THICKNESS;
-
-    let total_width_of_bricks = arena_width - 2. * GAP_BETWEEN_BRICKS_AND_SIDES;
-    let bottom_edge_of_bricks = (window_height / 2.0 * -1.0) + GAP_BETWEEN_PADDLE_AND_BRICKS;
-    let total_height_of_bricks =
-        (window_height / 2.0 + Y_OFFSET) - bottom_edge_of_bricks - GAP_BETWEEN_BRICKS_AND_CEILING;
-
-    assert!(total_width_of_bricks > 0.0);
-    assert!(total_height_of_bricks > 0.0);
-
-    // Given the space available, compute how many rows and columns of bricks we can fit
-    let n_columns = (total_width_of_bricks / (BRICK_SIZE.x + GAP_BETWEEN_BRICKS)).floor() as usize;
-    let n_rows = (total_height_of_bricks / (BRICK_SIZE.y + GAP_BETWEEN_BRICKS)).floor() as usize;
-    let n_vertical_gaps = n_columns - 1;
-
-    // Because we need to round the number of columns,
-    // the space on the top and sides of the bricks only captures a lower bound, not an exact value
-    let left_edge_of_bricks = 0.0
-        // Space taken up by the bricks
-        - (n_columns as f32 / 2.0 * BRICK_SIZE.x)
-        // Space taken up by the gaps
-        - n_vertical_gaps as f32 / 2.0 * GAP_BETWEEN_BRICKS;
-
-    // In Bevy, the `translation` of an entity describes the center point,
-    // not its bottom-left corner
-    let offset_x = left_edge_of_bricks + BRICK_SIZE.x / 2.;
-    let offset_y = bottom_edge_of_bricks + BRICK_SIZE.y / 2.;
-
-    for row in 0..n_rows {
-        for column in 0..n_columns {
-            let brick_position = Vec2::new(
-                offset_x + column as f32 * (BRICK_SIZE.x + GAP_BETWEEN_BRICKS),
-                offset_y + row as f32 * (BRICK_SIZE.y + GAP_BETWEEN_BRICKS),
-            );
+// Endless and versus games bring their own bricks, see `endless` and `versus`
+fn render_bricks(mut commands: Commands, game_config: Res<GameConfig>, theme: Res<Theme>) {
+    if matches!(game_config.mode, GameMode::Endless | GameMode::Versus) {
+        return;
+    }
+
+    let layout = game_config.layout.clone().unwrap_or_else(|| {
+        LevelLayout::generated(game_config.starting_level, &BrickGrid::default())
+    });
+    spawn_bricks(&mut commands, &theme, &layout);
+}
+
+fn render_next_level_bricks(
+    mut commands: Commands,
+    game_config: Res<GameConfig>,
+    theme: Res<Theme>,
+    mut levels_cleared: EventReader<LevelCleared>,
+) {
+    if let Some(level_cleared) = levels_cleared.iter().last() {
+        if matches!(game_config.mode, GameMode::Endless | GameMode::Versus) {
+            return;
+        }
+
+        let layout = LevelLayout::generated(level_cleared.level + 1, &BrickGrid::default());
+        spawn_bricks(&mut commands, &theme, &layout);
+    }
+}
+
+// Keeps bricks looking like what they are, including their pattern overlay. Bricks that survive a
+// hit can look different, e.g. strong bricks look like normal ones after their first hit.
+fn update_brick_looks(
+    mut commands: Commands,
+    theme: Res<Theme>,
+    mut brick_query: Query<(Entity, &Brick, &mut Sprite, &mut Handle<Image>), Changed<Brick>>,
+) {
+    for (entity, brick, mut sprite, mut texture) in brick_query.iter_mut() {
+        apply_brick_look(
+            &mut commands,
+            &theme,
+            entity,
+            brick.kind,
+            brick.hits_left,
+            &mut sprite,
+            &mut texture,
+        );
+    }
+}
+
+// Also used by the level editor, whose bricks aren't real ones
+pub(super) fn apply_brick_look(
+    commands: &mut Commands,
+    theme: &Theme,
+    entity: Entity,
+    kind: BrickKind,
+    hits_left: u32,
+    sprite: &mut Sprite,
+    texture: &mut Handle<Image>,
+) {
+    let look = theme.brick(kind, hits_left);
+    sprite.color = look.color;
+    *texture = look.texture.clone();
 
-            // brick
-            commands
-                .spawn()
-                .insert(Brick)
-                .insert_bundle(SpriteBundle {
-                    sprite: Sprite {
-                        color: BRICK_COLOR,
-                        ..default()
-                    },
-                    transform: Transform {
-                        translation: brick_position.extend(0.0),
-                        scale: Vec3::new(BRICK_SIZE.x, BRICK_SIZE.y, 1.0),
-                        ..default()
-                    },
+    commands.entity(entity).despawn_descendants();
+    if let Some(pattern) = theme.brick_pattern(kind, hits_left) {
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn_bundle(SpriteBundle {
+                sprite: Sprite {
+                    custom_size: Some(Vec2::ONE),
                     ..default()
-                })
-                .insert(Collider);
+                },
+                texture: pattern.clone(),
+                // just above the brick, whose scale gives it its size
+                transform: Transform::from_xyz(0.0, 0.0, 0.1),
+                ..default()
+            });
+        });
+    }
+}
+
+// Where bricks can go: a grid over the top part of the arena, sized to fit as many bricks as it can
+pub struct BrickGrid {
+    pub n_columns: usize,
+    pub n_rows: usize,
+    // center of the bottom left cell
+    offset: Vec2,
+}
+
+impl Default for BrickGrid {
+    fn default() -> Self {
+        let arena_width = (LOGICAL_WIDTH / 2.0 + X_OFFSET) * 2.0; // + WALL_THICKNESS;
+
+        let total_width_of_bricks = arena_width - 2. * GAP_BETWEEN_BRICKS_AND_SIDES;
+        let bottom_edge_of_bricks = -(LOGICAL_HEIGHT / 2.0) + GAP_BETWEEN_PADDLE_AND_BRICKS;
+        let total_height_of_bricks = (LOGICAL_HEIGHT / 2.0 + Y_OFFSET)
+            - bottom_edge_of_bricks
+            - GAP_BETWEEN_BRICKS_AND_CEILING;
+
+        assert!(total_width_of_bricks > 0.0);
+        assert!(total_height_of_bricks > 0.0);
+
+        // Given the space available, compute how many rows and columns of bricks we can fit
+        let n_columns =
+            (total_width_of_bricks / (BRICK_SIZE.x + GAP_BETWEEN_BRICKS)).floor() as usize;
+        let n_rows =
+            (total_height_of_bricks / (BRICK_SIZE.y + GAP_BETWEEN_BRICKS)).floor() as usize;
+        let n_vertical_gaps = n_columns - 1;
+
+        // Because we need to round the number of columns,
+        // the space on the top and sides of the bricks only captures a lower bound, not an exact value
+        let left_edge_of_bricks = 0.0
+            // Space taken up by the bricks
+            - (n_columns as f32 / 2.0 * BRICK_SIZE.x)
+            // Space taken up by the gaps
+            - n_vertical_gaps as f32 / 2.0 * GAP_BETWEEN_BRICKS;
+
+        // In Bevy, the `translation` of an entity describes the center point,
+        // not its bottom-left corner
+        BrickGrid {
+            n_columns,
+            n_rows,
+            offset: Vec2::new(
+                left_edge_of_bricks + BRICK_SIZE.x / 2.,
+                bottom_edge_of_bricks + BRICK_SIZE.y / 2.,
+            ),
+        }
+    }
+}
+
+impl BrickGrid {
+    pub const CELL_SIZE: Vec2 = BRICK_SIZE;
+
+    // Rows are counted from the top, like in level layouts
+    pub fn cell_position(&self, row: usize, column: usize) -> Vec2 {
+        let row_from_bottom = self.n_rows - 1 - row;
+        Vec2::new(
+            self.offset.x + column as f32 * (BRICK_SIZE.x + GAP_BETWEEN_BRICKS),
+            self.offset.y + row_from_bottom as f32 * (BRICK_SIZE.y + GAP_BETWEEN_BRICKS),
+        )
+    }
+
+    // The cell under a point, gaps count as part of the nearest cell
+    pub fn cell_at(&self, position: Vec2) -> Option<(usize, usize)> {
+        let step = BRICK_SIZE + Vec2::splat(GAP_BETWEEN_BRICKS);
+        let cell = ((position - self.offset) / step + Vec2::splat(0.5)).floor();
+        if cell.x < 0.0 || cell.y < 0.0 {
+            return None;
         }
+
+        let (column, row_from_bottom) = (cell.x as usize, cell.y as usize);
+        if column >= self.n_columns || row_from_bottom >= self.n_rows {
+            return None;
+        }
+        Some((self.n_rows - 1 - row_from_bottom, column))
     }
 }
+
+pub(super) fn spawn_bricks(commands: &mut Commands, theme: &Theme, layout: &LevelLayout) {
+    let grid = BrickGrid::default();
+
+    for row in 0..grid.n_rows {
+        for column in 0..grid.n_columns {
+            if let Some(kind) = layout.get(row, column) {
+                spawn_brick(
+                    commands,
+                    theme,
+                    kind,
+                    row as u32,
+                    grid.cell_position(row, column),
+                );
+            }
+        }
+    }
+}
+
+pub(super) fn spawn_brick(
+    commands: &mut Commands,
+    theme: &Theme,
+    kind: BrickKind,
+    row: u32,
+    position: Vec2,
+) {
+    commands
+        .spawn()
+        .insert(Brick {
+            kind,
+            hits_left: kind.hits(),
+            row,
+        })
+        .insert(GameEntity)
+        .insert_bundle(theme.brick(kind, kind.hits()).sprite_bundle(Transform {
+            translation: position.extend(0.0),
+            scale: Vec3::new(BRICK_SIZE.x, BRICK_SIZE.y, 1.0),
+            ..default()
+        }))
+        .insert(Collider);
+}
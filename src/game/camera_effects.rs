@@ -0,0 +1,101 @@
+use bevy::{prelude::*, render::camera::Camera2d};
+use rand::Rng;
+
+use crate::{settings::Settings, GameState};
+
+use super::{
+    clock::GameClock,
+    events::{BallLost, BrickDestroyed, CollisionCheck},
+    scoring::{Combo, ScoreUpdate},
+};
+
+// How far the camera can move away from the center at full trauma, in logical units
+const MAX_SHAKE_OFFSET: f32 = 12.0;
+// Trauma lost per second, so a full shake settles in a bit under a second
+const TRAUMA_DECAY: f32 = 1.5;
+const BRICK_TRAUMA: f32 = 0.25;
+const BALL_LOST_TRAUMA: f32 = 0.7;
+const HIT_STOP_DURATION: f32 = 0.06;
+
+// Shake grows with the square of the trauma, so small knocks stay subtle
+#[derive(Default)]
+struct ScreenShake {
+    trauma: f32,
+}
+
+// Screen shake on brick breaks and lost balls, and hit-stop on combos. Both can be turned down
+// or off from the settings.
+pub struct CameraEffectsPlugin;
+
+impl Plugin for CameraEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScreenShake>()
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(add_trauma.after(CollisionCheck))
+                    .with_system(hit_stop_on_combo.after(ScoreUpdate))
+                    .with_system(shake_camera.after(add_trauma)),
+            )
+            .add_system_set(SystemSet::on_pause(GameState::InGame).with_system(reset_camera))
+            .add_system_set(SystemSet::on_exit(GameState::InGame).with_system(reset_effects));
+    }
+}
+
+fn add_trauma(
+    mut shake: ResMut<ScreenShake>,
+    mut bricks_destroyed: EventReader<BrickDestroyed>,
+    mut balls_lost: EventReader<BallLost>,
+) {
+    let trauma = bricks_destroyed.iter().count() as f32 * BRICK_TRAUMA
+        + balls_lost.iter().count() as f32 * BALL_LOST_TRAUMA;
+    if trauma > 0.0 {
+        shake.trauma = (shake.trauma + trauma).min(1.0);
+    }
+}
+
+// Freezes every time the combo multiplier goes up
+fn hit_stop_on_combo(
+    settings: Res<Settings>,
+    combo: Res<Combo>,
+    mut clock: ResMut<GameClock>,
+    mut last_multiplier: Local<u32>,
+) {
+    if settings.hit_stop && combo.multiplier() > (*last_multiplier).max(1) {
+        clock.hit_stop(HIT_STOP_DURATION);
+    }
+    *last_multiplier = combo.multiplier();
+}
+
+// The game camera sits at the origin, so the shake offset is its whole position
+fn shake_camera(
+    clock: Res<GameClock>,
+    settings: Res<Settings>,
+    mut shake: ResMut<ScreenShake>,
+    mut camera_query: Query<&mut Transform, With<Camera2d>>,
+) {
+    if shake.trauma <= 0.0 {
+        return;
+    }
+    shake.trauma = (shake.trauma - TRAUMA_DECAY * clock.delta_seconds()).max(0.0);
+
+    // cosmetic only, so this doesn't take from the seeded game rng
+    let mut rng = rand::thread_rng();
+    let strength = MAX_SHAKE_OFFSET * settings.screen_shake * shake.trauma * shake.trauma;
+    let offset = Vec2::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)) * strength;
+
+    for mut transform in camera_query.iter_mut() {
+        transform.translation.x = offset.x;
+        transform.translation.y = offset.y;
+    }
+}
+
+fn reset_camera(mut camera_query: Query<&mut Transform, With<Camera2d>>) {
+    for mut transform in camera_query.iter_mut() {
+        transform.translation.x = 0.0;
+        transform.translation.y = 0.0;
+    }
+}
+
+fn reset_effects(mut shake: ResMut<ScreenShake>) {
+    *shake = ScreenShake::default();
+}
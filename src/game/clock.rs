@@ -0,0 +1,62 @@
+use bevy::{core::CoreSystem, prelude::*};
+
+// Time step used by the gameplay systems. It follows `Time` when playing normally, but can be
+// pinned to a fixed step (headless runs) or driven by a replay so the simulation is repeatable.
+pub struct GameClock {
+    delta: f32,
+    fixed_step: Option<f32>,
+    // seconds left during which the game stands still, see `hit_stop`
+    frozen_for: f32,
+}
+
+impl GameClock {
+    pub fn fixed(step: f32) -> Self {
+        GameClock {
+            delta: step,
+            fixed_step: Some(step),
+            frozen_for: 0.0,
+        }
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta
+    }
+
+    pub fn set_delta(&mut self, delta: f32) {
+        self.delta = delta;
+    }
+
+    // Freezes the game for a moment, starting next frame
+    pub fn hit_stop(&mut self, duration: f32) {
+        self.frozen_for = self.frozen_for.max(duration);
+    }
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        GameClock {
+            delta: 0.0,
+            fixed_step: None,
+            frozen_for: 0.0,
+        }
+    }
+}
+
+pub struct ClockPlugin;
+
+impl Plugin for ClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameClock>()
+            .add_system_to_stage(CoreStage::First, update_game_clock.after(CoreSystem::Time));
+    }
+}
+
+fn update_game_clock(time: Res<Time>, mut clock: ResMut<GameClock>) {
+    let step = clock.fixed_step.unwrap_or_else(|| time.delta_seconds());
+    if clock.frozen_for > 0.0 {
+        clock.frozen_for -= step;
+        clock.delta = 0.0;
+    } else {
+        clock.delta = step;
+    }
+}
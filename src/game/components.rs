@@ -1,5 +1,13 @@
 use bevy::prelude::Component;
 
+use super::{
+    bricks::BrickKind,
+    difficulty::{Difficulty, DifficultyParams},
+    game_mode::GameMode,
+    level_layout::LevelLayout,
+    speed_up::SpeedUpConfig,
+};
+
 #[derive(Component)]
 pub struct Collider;
 
@@ -7,14 +15,52 @@ pub struct Collider;
 pub struct GameEntity;
 
 #[derive(Component)]
-pub struct Brick;
-
-#[derive(Component)]
-pub struct Scoreboard;
+pub struct Brick {
+    pub kind: BrickKind,
+    pub hits_left: u32,
+    // counted from the top, starting at 0
+    pub row: u32,
+}
 
+// Losing the ball here costs the player a life
 #[derive(Component)]
-pub struct FailZone;
+pub struct FailZone {
+    pub player: usize,
+}
 
 pub struct GameData {
     pub score: i32,
+    pub level: u32,
+    pub lives: u32,
+}
+
+// How a new game should be set up, filled in from the command line and the main menu
+pub struct GameConfig {
+    pub mode: GameMode,
+    pub starting_level: u32,
+    pub difficulty: Difficulty,
+    // used when the difficulty is `Custom`
+    pub custom_difficulty: DifficultyParams,
+    // played instead of the starting level's own bricks, from a level file or the editor
+    pub layout: Option<LevelLayout>,
+    pub speed_up: SpeedUpConfig,
+}
+
+impl GameConfig {
+    pub fn difficulty_params(&self) -> DifficultyParams {
+        self.difficulty.params(&self.custom_difficulty)
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        GameConfig {
+            mode: GameMode::Classic,
+            starting_level: 1,
+            difficulty: Difficulty::Normal,
+            custom_difficulty: DifficultyParams::default(),
+            layout: None,
+            speed_up: SpeedUpConfig::default(),
+        }
+    }
 }
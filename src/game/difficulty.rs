@@ -0,0 +1,104 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, ValueEnum, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard,
+    // uses the `custom_difficulty` values from the settings file
+    Custom,
+}
+
+// Everything a difficulty changes about a game
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DifficultyParams {
+    // multiplier for the ball's base speed
+    pub ball_speed: f32,
+    // how much faster the ball gets with every level, as a fraction of its first level speed
+    pub ball_acceleration: f32,
+    pub paddle_width: f32,
+    pub paddle_speed: f32,
+    pub lives: u32,
+    pub score_multiplier: f32,
+}
+
+impl Default for DifficultyParams {
+    fn default() -> Self {
+        DifficultyParams::NORMAL
+    }
+}
+
+impl DifficultyParams {
+    const EASY: DifficultyParams = DifficultyParams {
+        ball_speed: 0.8,
+        ball_acceleration: 0.05,
+        paddle_width: 160.0,
+        paddle_speed: 550.0,
+        lives: 5,
+        score_multiplier: 0.5,
+    };
+
+    const NORMAL: DifficultyParams = DifficultyParams {
+        ball_speed: 1.0,
+        ball_acceleration: 0.1,
+        paddle_width: 120.0,
+        paddle_speed: 600.0,
+        lives: 3,
+        score_multiplier: 1.0,
+    };
+
+    const HARD: DifficultyParams = DifficultyParams {
+        ball_speed: 1.25,
+        ball_acceleration: 0.15,
+        paddle_width: 90.0,
+        paddle_speed: 700.0,
+        lives: 2,
+        score_multiplier: 2.0,
+    };
+
+    // Keeps hand-edited values playable. Values that aren't numbers at all (NaN, infinity) get
+    // the normal difficulty's instead, clamping would let NaN through.
+    fn clamped(&self) -> DifficultyParams {
+        let normal = DifficultyParams::NORMAL;
+        let finite = |value: f32, fallback: f32| if value.is_finite() { value } else { fallback };
+        DifficultyParams {
+            ball_speed: finite(self.ball_speed, normal.ball_speed).clamp(0.25, 4.0),
+            ball_acceleration: finite(self.ball_acceleration, normal.ball_acceleration)
+                .clamp(0.0, 1.0),
+            paddle_width: finite(self.paddle_width, normal.paddle_width).clamp(20.0, 400.0),
+            paddle_speed: finite(self.paddle_speed, normal.paddle_speed).clamp(50.0, 3000.0),
+            lives: self.lives.clamp(1, 99),
+            score_multiplier: finite(self.score_multiplier, normal.score_multiplier)
+                .clamp(0.0, 10.0),
+        }
+    }
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Custom,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Custom => "Custom",
+        }
+    }
+
+    pub fn params(&self, custom: &DifficultyParams) -> DifficultyParams {
+        match self {
+            Difficulty::Easy => DifficultyParams::EASY,
+            Difficulty::Normal => DifficultyParams::NORMAL,
+            Difficulty::Hard => DifficultyParams::HARD,
+            Difficulty::Custom => custom.clamped(),
+        }
+    }
+}
@@ -0,0 +1,599 @@
+use std::path::PathBuf;
+
+use bevy::{prelude::*, render::texture::DEFAULT_IMAGE_HANDLE};
+
+use crate::{
+    scaling::{ScaledFont, ScaledSize, ViewScale},
+    state_plugin::StateChange,
+    theme::Theme,
+    utilities::{despawn_entities, MenuEvent, NORMAL_BUTTON, SELECTED_BUTTON, TEXT_COLOR},
+    GameState,
+};
+
+use super::{
+    bricks::{apply_brick_look, BrickGrid, BrickKind},
+    components::GameConfig,
+    game_mode::GameMode,
+    level_generator::LevelGenerator,
+    level_layout::LevelLayout,
+};
+
+const DEFAULT_LEVEL_FILE: &str = "level.ron";
+// Random levels are as tough as this level of the game
+const GENERATED_LEVEL: u32 = 3;
+
+const EMPTY_CELL_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.06);
+const HELP_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+const PANEL_TITLE_FONT_SIZE: f32 = 18.0;
+const BUTTON_FONT_SIZE: f32 = 16.0;
+const BUTTON_SIZE: Vec2 = bevy::math::const_vec2!([80.0, 30.0]);
+const BUTTON_MARGIN: f32 = 4.0;
+const HELP_FONT_SIZE: f32 = 16.0;
+// Distance from the window's edges, the panels sit in the strips left and right of the arena
+const PANEL_MARGIN: f32 = 8.0;
+
+// Where the editor saves the level to and loads it from
+pub struct LevelFile(pub PathBuf);
+
+impl Default for LevelFile {
+    fn default() -> Self {
+        LevelFile(PathBuf::from(DEFAULT_LEVEL_FILE))
+    }
+}
+
+// Present while a level from the editor is being play-tested, so the game comes back to the
+// editor when it is over. Ended on the way back to the editor or the main menu, whichever comes
+// first, so the level doesn't carry over into later games.
+pub struct PlayTest {
+    // the level and mode the game was set up with before the play-test
+    previous_layout: Option<LevelLayout>,
+    previous_mode: GameMode,
+}
+
+struct Editor {
+    layout: LevelLayout,
+    brush: BrickKind,
+    undo: Vec<LevelLayout>,
+    redo: Vec<LevelLayout>,
+    // the layout from before the mouse button went down, a whole stroke is undone at once
+    stroke_start: Option<LevelLayout>,
+    status: String,
+}
+
+impl Editor {
+    // Remembers the layout before a change, so the change can be undone
+    fn checkpoint(&mut self, layout: LevelLayout) {
+        self.undo.push(layout);
+        self.redo.clear();
+    }
+}
+
+#[derive(Component)]
+struct EditorEntity;
+
+// A spot in the brick grid, showing what the layout has there
+#[derive(Component)]
+struct EditorCell {
+    row: usize,
+    column: usize,
+    shown: Option<BrickKind>,
+}
+
+#[derive(Component)]
+struct StatusText;
+
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+enum EditorAction {
+    Brush(BrickKind),
+    Undo,
+    Redo,
+    Clear,
+    Generate,
+    Save,
+    Load,
+    PlayTest,
+    Back,
+}
+
+// Lays out bricks with the mouse on the same grid the game uses, and saves them to a level file
+// that can be played with `--level-file`
+pub struct EditorPlugin;
+
+impl Plugin for EditorPlugin {
+    fn build(&self, app: &mut App) {
+        let grid = BrickGrid::default();
+        app.init_resource::<LevelFile>()
+            .insert_resource(Editor {
+                layout: LevelLayout::empty(&grid),
+                brush: BrickKind::Normal,
+                undo: Vec::new(),
+                redo: Vec::new(),
+                stroke_start: None,
+                status: String::new(),
+            })
+            .add_system_set(
+                SystemSet::on_enter(GameState::Editor)
+                    .with_system(load_on_first_visit)
+                    .with_system(end_play_test)
+                    .with_system(render_editor),
+            )
+            .add_system_set(SystemSet::on_enter(GameState::MainMenu).with_system(end_play_test))
+            .add_system_set(
+                SystemSet::on_update(GameState::Editor)
+                    .with_system(handle_actions)
+                    .with_system(paint.after(handle_actions))
+                    .with_system(update_cells.after(paint))
+                    .with_system(update_panels.after(handle_actions)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Editor).with_system(despawn_entities::<EditorEntity>),
+            );
+    }
+}
+
+// The level file is read the first time the editor opens, later visits carry on where they were
+fn load_on_first_visit(
+    mut loaded: Local<bool>,
+    level_file: Res<LevelFile>,
+    mut editor: ResMut<Editor>,
+) {
+    if *loaded {
+        return;
+    }
+    *loaded = true;
+
+    if level_file.0.exists() {
+        load(&level_file, &mut editor);
+    }
+}
+
+fn end_play_test(
+    mut commands: Commands,
+    play_test: Option<Res<PlayTest>>,
+    mut game_config: ResMut<GameConfig>,
+) {
+    if let Some(play_test) = play_test {
+        game_config.layout = play_test.previous_layout.clone();
+        game_config.mode = play_test.previous_mode;
+        commands.remove_resource::<PlayTest>();
+    }
+}
+
+fn render_editor(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let grid = BrickGrid::default();
+
+    commands
+        .spawn_bundle(OrthographicCameraBundle::new_2d())
+        .insert(EditorEntity);
+    commands
+        .spawn_bundle(UiCameraBundle::default())
+        .insert(EditorEntity);
+
+    // the cells get their looks from `update_cells`
+    for row in 0..grid.n_rows {
+        for column in 0..grid.n_columns {
+            commands
+                .spawn_bundle(SpriteBundle {
+                    sprite: Sprite {
+                        color: EMPTY_CELL_COLOR,
+                        custom_size: Some(Vec2::ONE),
+                        ..default()
+                    },
+                    transform: Transform {
+                        translation: grid.cell_position(row, column).extend(0.0),
+                        scale: BrickGrid::CELL_SIZE.extend(1.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .insert(EditorCell {
+                    row,
+                    column,
+                    shown: None,
+                })
+                .insert(EditorEntity);
+        }
+    }
+
+    let panel = |left: bool| Style {
+        position_type: PositionType::Absolute,
+        position: if left {
+            Rect {
+                top: Val::Px(PANEL_MARGIN),
+                left: Val::Px(PANEL_MARGIN),
+                ..default()
+            }
+        } else {
+            Rect {
+                top: Val::Px(PANEL_MARGIN),
+                right: Val::Px(PANEL_MARGIN),
+                ..default()
+            }
+        },
+        flex_direction: FlexDirection::ColumnReverse,
+        align_items: AlignItems::Center,
+        ..default()
+    };
+    let panels = [
+        (
+            panel(true),
+            "Bricks",
+            vec![
+                ("1 Normal", EditorAction::Brush(BrickKind::Normal)),
+                ("2 Strong", EditorAction::Brush(BrickKind::Strong)),
+                ("3 Steel", EditorAction::Brush(BrickKind::Steel)),
+                ("4 Time", EditorAction::Brush(BrickKind::Time)),
+            ],
+        ),
+        (
+            panel(false),
+            "Level",
+            vec![
+                ("Undo", EditorAction::Undo),
+                ("Redo", EditorAction::Redo),
+                ("Clear", EditorAction::Clear),
+                ("Random", EditorAction::Generate),
+                ("Save", EditorAction::Save),
+                ("Load", EditorAction::Load),
+                ("Play", EditorAction::PlayTest),
+                ("Back", EditorAction::Back),
+            ],
+        ),
+    ];
+
+    for (style, title, buttons) in panels {
+        commands
+            .spawn_bundle(NodeBundle {
+                style,
+                color: Color::NONE.into(),
+                ..default()
+            })
+            .insert(EditorEntity)
+            .with_children(|parent| {
+                parent
+                    .spawn_bundle(TextBundle {
+                        style: Style {
+                            margin: Rect::all(Val::Px(BUTTON_MARGIN)),
+                            ..default()
+                        },
+                        text: Text::with_section(
+                            title,
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: PANEL_TITLE_FONT_SIZE,
+                                color: TEXT_COLOR,
+                            },
+                            Default::default(),
+                        ),
+                        ..default()
+                    })
+                    .insert(ScaledFont(PANEL_TITLE_FONT_SIZE));
+
+                for (label, action) in buttons {
+                    parent
+                        .spawn_bundle(ButtonBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(BUTTON_SIZE.x), Val::Px(BUTTON_SIZE.y)),
+                                margin: Rect::all(Val::Px(BUTTON_MARGIN)),
+                                justify_content: JustifyContent::Center,
+                                align_items: AlignItems::Center,
+                                ..default()
+                            },
+                            color: NORMAL_BUTTON.into(),
+                            ..default()
+                        })
+                        .insert(action)
+                        .insert(ScaledSize(BUTTON_SIZE))
+                        .with_children(|parent| {
+                            parent
+                                .spawn_bundle(TextBundle {
+                                    text: Text::with_section(
+                                        label,
+                                        TextStyle {
+                                            font: font.clone(),
+                                            font_size: BUTTON_FONT_SIZE,
+                                            color: TEXT_COLOR,
+                                        },
+                                        Default::default(),
+                                    ),
+                                    ..default()
+                                })
+                                .insert(ScaledFont(BUTTON_FONT_SIZE));
+                        });
+                }
+            });
+    }
+
+    // help and status, in the empty lower half of the arena
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: Rect {
+                    bottom: Val::Percent(20.0),
+                    left: Val::Px(0.0),
+                    right: Val::Px(0.0),
+                    ..default()
+                },
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(EditorEntity)
+        .with_children(|parent| {
+            let style = |color| TextStyle {
+                font: font.clone(),
+                font_size: HELP_FONT_SIZE,
+                color,
+            };
+            parent
+                .spawn_bundle(TextBundle {
+                    text: Text {
+                        sections: vec![
+                            TextSection {
+                                value: "Left click places a brick, right click removes one\n\
+                                        Ctrl+Z undo, Ctrl+Y redo, Ctrl+S save, Ctrl+O load\n\
+                                        G random level, F5 play, Esc back\n\n"
+                                    .to_string(),
+                                style: style(HELP_COLOR),
+                            },
+                            TextSection {
+                                value: String::new(),
+                                style: style(TEXT_COLOR),
+                            },
+                        ],
+                        alignment: TextAlignment {
+                            horizontal: HorizontalAlign::Center,
+                            ..default()
+                        },
+                    },
+                    ..default()
+                })
+                .insert(StatusText)
+                .insert(ScaledFont(HELP_FONT_SIZE));
+        });
+}
+
+fn ctrl_pressed(keyboard_input: &Input<KeyCode>) -> bool {
+    keyboard_input.any_pressed([KeyCode::LControl, KeyCode::RControl])
+}
+
+fn shift_pressed(keyboard_input: &Input<KeyCode>) -> bool {
+    keyboard_input.any_pressed([KeyCode::LShift, KeyCode::RShift])
+}
+
+fn keyboard_action(keyboard_input: &Input<KeyCode>) -> Option<EditorAction> {
+    let ctrl = ctrl_pressed(keyboard_input);
+    let pressed = |key| keyboard_input.just_pressed(key);
+
+    if ctrl && pressed(KeyCode::Z) {
+        if shift_pressed(keyboard_input) {
+            Some(EditorAction::Redo)
+        } else {
+            Some(EditorAction::Undo)
+        }
+    } else if ctrl && pressed(KeyCode::Y) {
+        Some(EditorAction::Redo)
+    } else if ctrl && pressed(KeyCode::S) {
+        Some(EditorAction::Save)
+    } else if ctrl && pressed(KeyCode::O) {
+        Some(EditorAction::Load)
+    } else if pressed(KeyCode::Key1) {
+        Some(EditorAction::Brush(BrickKind::Normal))
+    } else if pressed(KeyCode::Key2) {
+        Some(EditorAction::Brush(BrickKind::Strong))
+    } else if pressed(KeyCode::Key3) {
+        Some(EditorAction::Brush(BrickKind::Steel))
+    } else if pressed(KeyCode::Key4) {
+        Some(EditorAction::Brush(BrickKind::Time))
+    } else if pressed(KeyCode::G) {
+        Some(EditorAction::Generate)
+    } else if pressed(KeyCode::F5) {
+        Some(EditorAction::PlayTest)
+    } else if pressed(KeyCode::Escape) {
+        Some(EditorAction::Back)
+    } else {
+        None
+    }
+}
+
+fn load(level_file: &LevelFile, editor: &mut Editor) {
+    match LevelLayout::load(&level_file.0, &BrickGrid::default()) {
+        Ok(layout) => {
+            if layout != editor.layout {
+                let previous = std::mem::replace(&mut editor.layout, layout);
+                editor.checkpoint(previous);
+            }
+            editor.status = format!("Loaded {}", level_file.0.display());
+        }
+        Err(err) => {
+            editor.status = format!("Could not load {}: {}", level_file.0.display(), err);
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn handle_actions(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    level_file: Res<LevelFile>,
+    level_generator: Res<LevelGenerator>,
+    mut editor: ResMut<Editor>,
+    mut game_config: ResMut<GameConfig>,
+    mut event_state_change: EventWriter<StateChange>,
+    mut menu_events: EventWriter<MenuEvent>,
+    button_query: Query<(&Interaction, &EditorAction), Changed<Interaction>>,
+) {
+    let clicked = button_query
+        .iter()
+        .find(|(interaction, _)| **interaction == Interaction::Clicked)
+        .map(|(_, action)| *action);
+    let action = match clicked.or_else(|| keyboard_action(&keyboard_input)) {
+        Some(action) => action,
+        None => return,
+    };
+
+    menu_events.send(MenuEvent::Selected);
+    match action {
+        EditorAction::Brush(kind) => editor.brush = kind,
+        EditorAction::Undo => {
+            if let Some(layout) = editor.undo.pop() {
+                let current = std::mem::replace(&mut editor.layout, layout);
+                editor.redo.push(current);
+            }
+        }
+        EditorAction::Redo => {
+            if let Some(layout) = editor.redo.pop() {
+                let current = std::mem::replace(&mut editor.layout, layout);
+                editor.undo.push(current);
+            }
+        }
+        EditorAction::Clear => {
+            let empty = LevelLayout::empty(&BrickGrid::default());
+            if editor.layout != empty {
+                let previous = std::mem::replace(&mut editor.layout, empty);
+                editor.checkpoint(previous);
+            }
+        }
+        EditorAction::Generate => {
+            let layout =
+                level_generator.generate(rand::random(), GENERATED_LEVEL, &BrickGrid::default());
+            let previous = std::mem::replace(&mut editor.layout, layout);
+            editor.checkpoint(previous);
+        }
+        EditorAction::Save => {
+            editor.status = match editor.layout.save(&level_file.0) {
+                Ok(()) => format!("Saved {}", level_file.0.display()),
+                Err(err) => format!("Could not save {}: {}", level_file.0.display(), err),
+            };
+        }
+        EditorAction::Load => load(&level_file, &mut editor),
+        EditorAction::PlayTest => {
+            // a level without breakable bricks can never be cleared
+            if !editor.layout.has_breakable_bricks() {
+                editor.status = "Place a brick that can be broken first".to_string();
+                return;
+            }
+
+            commands.insert_resource(PlayTest {
+                previous_layout: game_config.layout.take(),
+                previous_mode: game_config.mode,
+            });
+            // played as a classic game, other modes lay out bricks of their own
+            game_config.layout = Some(editor.layout.clone());
+            game_config.mode = GameMode::Classic;
+            event_state_change.send(StateChange::Set(GameState::InGame));
+        }
+        EditorAction::Back => {
+            event_state_change.send(StateChange::Set(GameState::MainMenu));
+        }
+    }
+}
+
+// Left click places bricks and right click removes them, for as long as the button is held
+fn paint(
+    windows: Res<Windows>,
+    view_scale: Res<ViewScale>,
+    mouse_input: Res<Input<MouseButton>>,
+    mut editor: ResMut<Editor>,
+) {
+    if mouse_input.any_just_pressed([MouseButton::Left, MouseButton::Right]) {
+        editor.stroke_start = Some(editor.layout.clone());
+    }
+
+    let brick = if mouse_input.pressed(MouseButton::Left) {
+        Some(editor.brush)
+    } else if mouse_input.pressed(MouseButton::Right) {
+        None
+    } else {
+        // the stroke is over, it is undone in one go
+        if editor.stroke_start.is_some() {
+            let stroke_start = editor.stroke_start.take().unwrap();
+            if stroke_start != editor.layout {
+                editor.checkpoint(stroke_start);
+            }
+        }
+        return;
+    };
+
+    let cursor = match windows
+        .get_primary()
+        .and_then(|window| window.cursor_position().map(|cursor| (window, cursor)))
+    {
+        // the camera sits at the center of the window, see `scaling`
+        Some((window, cursor)) => {
+            (cursor - Vec2::new(window.width(), window.height()) / 2.0) / view_scale.0
+        }
+        None => return,
+    };
+
+    if let Some((row, column)) = BrickGrid::default().cell_at(cursor) {
+        if editor.layout.get(row, column) != brick {
+            editor.layout.set(row, column, brick);
+        }
+    }
+}
+
+fn update_cells(
+    mut commands: Commands,
+    theme: Res<Theme>,
+    editor: Res<Editor>,
+    mut cell_query: Query<(Entity, &mut EditorCell, &mut Sprite, &mut Handle<Image>)>,
+) {
+    for (entity, mut cell, mut sprite, mut texture) in cell_query.iter_mut() {
+        let brick = editor.layout.get(cell.row, cell.column);
+        // bricks get their overlay patterns respawned, so only cells that changed are touched
+        if cell.shown == brick && !theme.is_changed() {
+            continue;
+        }
+        cell.shown = brick;
+
+        match brick {
+            Some(kind) => apply_brick_look(
+                &mut commands,
+                &theme,
+                entity,
+                kind,
+                kind.hits(),
+                &mut sprite,
+                &mut texture,
+            ),
+            None => {
+                sprite.color = EMPTY_CELL_COLOR;
+                *texture = DEFAULT_IMAGE_HANDLE.typed();
+                commands.entity(entity).despawn_descendants();
+            }
+        }
+    }
+}
+
+fn update_panels(
+    editor: Res<Editor>,
+    mut button_query: Query<(&EditorAction, &mut UiColor)>,
+    mut status_query: Query<&mut Text, With<StatusText>>,
+) {
+    if !editor.is_changed() {
+        return;
+    }
+
+    for (action, mut color) in button_query.iter_mut() {
+        let selected = *action == EditorAction::Brush(editor.brush);
+        let wanted: UiColor = if selected {
+            SELECTED_BUTTON.into()
+        } else {
+            NORMAL_BUTTON.into()
+        };
+        if color.0 != wanted.0 {
+            *color = wanted;
+        }
+    }
+
+    for mut text in status_query.iter_mut() {
+        if text.sections[1].value != editor.status {
+            text.sections[1].value = editor.status.clone();
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use bevy::prelude::*;
+
+use crate::{scaling::LOGICAL_WIDTH, theme::Theme, GameState};
+
+use super::{
+    bricks::{self, BrickGrid},
+    clock::GameClock,
+    components::{Brick, GameConfig, GameData, GameEntity},
+    events::{CollisionCheck, LevelCleared},
+    game_mode::GameMode,
+    game_state::lose_life,
+    level_generator::LevelGenerator,
+    level_layout::LevelLayout,
+    rng::GameRng,
+    walls::X_OFFSET,
+};
+
+// Rows of bricks a game starts with, and starts over with after clearing the arena
+const STARTING_ROWS: usize = 4;
+// Time between new rows on the first level, every level after it is a bit quicker
+const ROW_INTERVAL: f32 = 12.0;
+const ROW_INTERVAL_FACTOR: f32 = 0.85;
+const MIN_ROW_INTERVAL: f32 = 4.0;
+// The level goes up every this many new rows
+const ROWS_PER_LEVEL: u32 = 6;
+// The game is over once a brick reaches down to here, a bit above the ball's serve
+const DANGER_LINE_Y: f32 = -120.0;
+const DANGER_LINE_THICKNESS: f32 = 3.0;
+const DANGER_LINE_COLOR: Color = Color::rgba(1.0, 0.2, 0.2, 0.5);
+
+#[derive(Default)]
+struct EndlessState {
+    // rows waiting to come down, the next one last
+    pending_rows: Vec<String>,
+    // layouts the rows have been taken from so far
+    layouts_used: u32,
+    rows_added: u32,
+    until_next_row: f32,
+}
+
+impl EndlessState {
+    // Rows come from generated levels, bottom row first, so their patterns scroll into view.
+    // Empty rows are left out, every new row has something in it.
+    fn next_row(&mut self, seed: u64, level: u32, generator: &LevelGenerator) -> String {
+        loop {
+            if let Some(row) = self.pending_rows.pop() {
+                if row.chars().any(|symbol| symbol != '.') {
+                    return row;
+                }
+                continue;
+            }
+
+            let layout = generator.generate(
+                seed ^ self.layouts_used as u64,
+                level,
+                &BrickGrid::default(),
+            );
+            self.layouts_used += 1;
+            self.pending_rows = layout.rows;
+        }
+    }
+}
+
+// Endless mode: instead of levels, new rows of bricks keep coming in at the top and push the
+// others down toward the paddle, a bit quicker every level. The game is over when a brick reaches
+// the danger line, or as usual when the ball is lost too often.
+pub struct EndlessPlugin;
+
+impl Plugin for EndlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EndlessState>()
+            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(start_endless))
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(refill_cleared_arena.after(CollisionCheck))
+                    .with_system(add_rows.after(CollisionCheck))
+                    .with_system(check_danger_line.after(add_rows).after(lose_life)),
+            );
+    }
+}
+
+fn start_endless(
+    mut commands: Commands,
+    game_config: Res<GameConfig>,
+    theme: Res<Theme>,
+    rng: Res<GameRng>,
+    generator: Res<LevelGenerator>,
+    mut state: ResMut<EndlessState>,
+) {
+    if game_config.mode != GameMode::Endless {
+        return;
+    }
+
+    // `GameData` may not have been reset for the new game yet
+    let level = game_config.starting_level;
+    *state = EndlessState {
+        until_next_row: row_interval(level),
+        ..default()
+    };
+
+    // a level file or the level editor's layout is where the game starts from
+    match &game_config.layout {
+        Some(layout) => bricks::spawn_bricks(&mut commands, &theme, layout),
+        None => spawn_starting_rows(&mut commands, &theme, &mut state, &rng, &generator, level),
+    }
+
+    let arena_width = (LOGICAL_WIDTH / 2.0 + X_OFFSET) * 2.0;
+    commands
+        .spawn_bundle(SpriteBundle {
+            sprite: Sprite {
+                color: DANGER_LINE_COLOR,
+                custom_size: Some(Vec2::ONE),
+                ..default()
+            },
+            transform: Transform {
+                translation: Vec3::new(0.0, DANGER_LINE_Y, -1.0),
+                scale: Vec3::new(arena_width, DANGER_LINE_THICKNESS, 1.0),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(GameEntity);
+}
+
+fn spawn_starting_rows(
+    commands: &mut Commands,
+    theme: &Theme,
+    state: &mut EndlessState,
+    rng: &GameRng,
+    generator: &LevelGenerator,
+    level: u32,
+) {
+    let grid = BrickGrid::default();
+    let mut layout = LevelLayout::empty(&grid);
+    // the first row to come down goes at the bottom
+    for row in (0..STARTING_ROWS).rev() {
+        layout.rows[row] = state.next_row(rng.seed(), level, generator);
+    }
+    bricks::spawn_bricks(commands, theme, &layout);
+}
+
+fn row_interval(level: u32) -> f32 {
+    (ROW_INTERVAL * ROW_INTERVAL_FACTOR.powi(level.saturating_sub(1) as i32)).max(MIN_ROW_INTERVAL)
+}
+
+// Clearing every breakable brick still earns the level bonuses, then the arena starts over with
+// a few fresh rows. Steel bricks left behind go with the old rows.
+#[allow(clippy::too_many_arguments)]
+fn refill_cleared_arena(
+    mut commands: Commands,
+    game_config: Res<GameConfig>,
+    game_data: Res<GameData>,
+    theme: Res<Theme>,
+    rng: Res<GameRng>,
+    generator: Res<LevelGenerator>,
+    mut state: ResMut<EndlessState>,
+    mut levels_cleared: EventReader<LevelCleared>,
+    brick_query: Query<Entity, With<Brick>>,
+) {
+    if levels_cleared.iter().count() == 0 || game_config.mode != GameMode::Endless {
+        return;
+    }
+
+    for brick in brick_query.iter() {
+        commands.entity(brick).despawn_recursive();
+    }
+    spawn_starting_rows(
+        &mut commands,
+        &theme,
+        &mut state,
+        &rng,
+        &generator,
+        game_data.level,
+    );
+    state.until_next_row = row_interval(game_data.level);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn add_rows(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    game_config: Res<GameConfig>,
+    mut game_data: ResMut<GameData>,
+    theme: Res<Theme>,
+    rng: Res<GameRng>,
+    generator: Res<LevelGenerator>,
+    mut state: ResMut<EndlessState>,
+    mut brick_query: Query<(&mut Brick, &mut Transform)>,
+) {
+    if game_config.mode != GameMode::Endless {
+        return;
+    }
+
+    state.until_next_row -= clock.delta_seconds();
+    if state.until_next_row > 0.0 {
+        return;
+    }
+
+    // everything moves down a row to make room at the top
+    let grid = BrickGrid::default();
+    let row_height = grid.cell_position(0, 0).y - grid.cell_position(1, 0).y;
+    for (mut brick, mut transform) in brick_query.iter_mut() {
+        brick.row += 1;
+        transform.translation.y -= row_height;
+    }
+
+    let row = state.next_row(rng.seed(), game_data.level, &generator);
+    let layout = LevelLayout { rows: vec![row] };
+    for column in 0..grid.n_columns {
+        if let Some(kind) = layout.get(0, column) {
+            bricks::spawn_brick(
+                &mut commands,
+                &theme,
+                kind,
+                0,
+                grid.cell_position(0, column),
+            );
+        }
+    }
+
+    state.rows_added += 1;
+    if state.rows_added.is_multiple_of(ROWS_PER_LEVEL) {
+        game_data.level += 1;
+    }
+    state.until_next_row += row_interval(game_data.level);
+}
+
+fn check_danger_line(
+    game_config: Res<GameConfig>,
+    mut app_state: ResMut<State<GameState>>,
+    brick_query: Query<&Transform, With<Brick>>,
+) {
+    if game_config.mode != GameMode::Endless {
+        return;
+    }
+
+    let reached = brick_query
+        .iter()
+        .any(|transform| transform.translation.y - transform.scale.y / 2.0 <= DANGER_LINE_Y);
+    if reached {
+        // losing the last life in the same frame has already ended the game
+        let _ = app_state.set(GameState::GameOver);
+    }
+}
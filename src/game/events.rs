@@ -0,0 +1,68 @@
+use bevy::prelude::*;
+
+use super::bricks::BrickKind;
+
+// Things that happen during play, sent by the collision checks. Scoring, state changes, sound
+// and effects all react to these rather than being handled inline by the physics.
+
+// Label of the collision check, systems reading the events should run after it to react in the
+// same frame
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CollisionCheck;
+
+// Label of the system serving the ball again after a lost life or a cleared level
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BallReset;
+
+// Positions are where the ball was when the event happened, unless noted otherwise
+
+pub struct BallHitPaddle {
+    pub position: Vec2,
+    // whose paddle it was
+    pub player: usize,
+}
+
+pub struct BallHitWall {
+    pub position: Vec2,
+}
+
+// A brick that took the hit without breaking, a strong one with hits left or steel
+pub struct BrickHit {
+    pub position: Vec2,
+    pub kind: BrickKind,
+}
+
+pub struct BrickDestroyed {
+    // the position of the brick
+    pub position: Vec2,
+    pub kind: BrickKind,
+    // counted from the top, starting at 0
+    pub row: u32,
+    pub points: i32,
+}
+
+pub struct BallLost {
+    pub position: Vec2,
+    // whose side the ball went out on
+    pub player: usize,
+}
+
+pub struct LevelCleared {
+    pub level: u32,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ScoreReason {
+    Brick,
+    // bonuses for clearing a level
+    QuickClear,
+    Flawless,
+}
+
+// Sent by the scoring (rather than the collision checks) whenever points are added
+pub struct PointsScored {
+    // where the points came from, like the brick that was broken
+    pub position: Vec2,
+    pub points: i32,
+    pub reason: ScoreReason,
+}
@@ -0,0 +1,45 @@
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, ValueEnum, Serialize, Deserialize)]
+pub enum GameMode {
+    // clear the levels one by one
+    #[default]
+    Classic,
+    // new rows of bricks keep coming down from the top
+    Endless,
+    // as many bricks as possible before the clock runs out
+    TimeAttack,
+    // two players on one keyboard, one paddle at the bottom and one at the top
+    Versus,
+    // two players on one keyboard, with a paddle each at different heights and shared lives
+    Coop,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 5] = [
+        GameMode::Classic,
+        GameMode::Endless,
+        GameMode::TimeAttack,
+        GameMode::Versus,
+        GameMode::Coop,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameMode::Classic => "Classic",
+            GameMode::Endless => "Endless",
+            GameMode::TimeAttack => "Time attack",
+            GameMode::Versus => "Versus",
+            GameMode::Coop => "Co-op",
+        }
+    }
+
+    // Number of paddles, each with its own keys
+    pub fn players(&self) -> usize {
+        match self {
+            GameMode::Versus | GameMode::Coop => 2,
+            _ => 1,
+        }
+    }
+}
@@ -2,12 +2,28 @@ use crate::{state_plugin::StateChange, utilities::despawn_entities, GameState};
 use bevy::prelude::*;
 
 use super::{
+    autoplayer::AutoplayerPlugin,
     ball::BallPlugin,
     bricks::BricksPlugin,
-    components::{GameData, GameEntity, Scoreboard},
+    clock::ClockPlugin,
+    components::{GameConfig, GameData, GameEntity},
+    editor_state::EditorPlugin,
+    endless::EndlessPlugin,
+    events::{
+        BallHitPaddle, BallHitWall, BallLost, BrickDestroyed, BrickHit, CollisionCheck,
+        LevelCleared, PointsScored,
+    },
+    game_mode::GameMode,
+    hud::HudPlugin,
+    level_generator::LevelGenerator,
     lose_state::LosePlugin,
     paddle::PaddlePlugin,
     pause_state::PausePlugin,
+    rng::GameRng,
+    scoring::ScoringPlugin,
+    speed_up::SpeedUpPlugin,
+    time_attack::TimeAttackPlugin,
+    versus::VersusPlugin,
     walls::WallsPlugin,
 };
 
@@ -15,59 +31,66 @@ pub struct GamePlugin;
 
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(GameData { score: 0 })
-            .add_plugin(PaddlePlugin)
-            .add_plugin(BallPlugin)
-            .add_plugin(WallsPlugin)
-            .add_plugin(BricksPlugin)
-            .add_plugin(PausePlugin)
-            .add_plugin(LosePlugin)
-            // setup when entering the state
-            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(setup_game))
-            .add_system_set(
-                SystemSet::on_exit(GameState::InGame).with_system(despawn_entities::<GameEntity>),
-            )
-            .add_system_set(
-                SystemSet::on_update(GameState::InGame)
-                    .with_system(handle_pause_game)
-                    .with_system(update_score),
-            );
+        app.insert_resource(GameData {
+            score: 0,
+            level: 1,
+            lives: 0,
+        })
+        .init_resource::<GameConfig>()
+        .init_resource::<GameRng>()
+        .init_resource::<LevelGenerator>()
+        .add_event::<BallHitPaddle>()
+        .add_event::<BallHitWall>()
+        .add_event::<BrickHit>()
+        .add_event::<BrickDestroyed>()
+        .add_event::<BallLost>()
+        .add_event::<LevelCleared>()
+        .add_event::<PointsScored>()
+        .add_plugin(ClockPlugin)
+        .add_plugin(PaddlePlugin)
+        .add_plugin(BallPlugin)
+        .add_plugin(WallsPlugin)
+        .add_plugin(BricksPlugin)
+        .add_plugin(SpeedUpPlugin)
+        .add_plugin(ScoringPlugin)
+        .add_plugin(HudPlugin)
+        .add_plugin(PausePlugin)
+        .add_plugin(LosePlugin)
+        .add_plugin(EditorPlugin)
+        .add_plugin(EndlessPlugin)
+        .add_plugin(TimeAttackPlugin)
+        .add_plugin(VersusPlugin)
+        .add_plugin(AutoplayerPlugin)
+        // setup when entering the state
+        .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(setup_game))
+        .add_system_set(
+            SystemSet::on_exit(GameState::InGame).with_system(despawn_entities::<GameEntity>),
+        )
+        .add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(handle_pause_game)
+                .with_system(advance_level.after(CollisionCheck))
+                .with_system(lose_life.after(CollisionCheck)),
+        );
     }
 }
 
-const SCOREBOARD_FONT_SIZE: f32 = 40.0;
-const SCORE_COLOR: Color = Color::rgb(1.0, 0.5, 0.5);
-
-fn setup_game(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
-    commands.spawn_bundle(UiCameraBundle::default());
+fn setup_game(
+    mut commands: Commands,
+    game_config: Res<GameConfig>,
+    mut game_data: ResMut<GameData>,
+) {
+    *game_data = GameData {
+        score: 0,
+        level: game_config.starting_level,
+        lives: game_config.difficulty_params().lives,
+    };
 
-    // player one score
     commands
-        .spawn_bundle(TextBundle {
-            style: Style {
-                position_type: PositionType::Absolute,
-                position: Rect {
-                    top: Val::Px(15.0),
-                    left: Val::Px(15.0),
-                    ..default()
-                },
-                ..default()
-            },
-            text: Text {
-                sections: vec![TextSection {
-                    value: "0".to_string(),
-                    style: TextStyle {
-                        font_size: SCOREBOARD_FONT_SIZE,
-                        color: SCORE_COLOR,
-                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
-                    },
-                }],
-                ..default()
-            },
-            ..default()
-        })
-        .insert(Scoreboard)
+        .spawn_bundle(OrthographicCameraBundle::new_2d())
+        .insert(GameEntity);
+    commands
+        .spawn_bundle(UiCameraBundle::default())
         .insert(GameEntity);
 }
 
@@ -80,10 +103,31 @@ fn handle_pause_game(
     }
 }
 
-fn update_score(
-    game_data: Res<GameData>,
-    mut scoreboard_query: Query<&mut Text, With<Scoreboard>>,
+pub(super) fn advance_level(
+    mut levels_cleared: EventReader<LevelCleared>,
+    mut game_data: ResMut<GameData>,
 ) {
-    let mut scoreboard_text = scoreboard_query.single_mut();
-    scoreboard_text.sections[0].value = format!("{}", game_data.score);
+    if let Some(level_cleared) = levels_cleared.iter().last() {
+        game_data.level = level_cleared.level + 1;
+    }
+}
+
+// The ball gets served again (see `ball`) until there are no lives left. Versus keeps the lives
+// of each player itself.
+pub(super) fn lose_life(
+    game_config: Res<GameConfig>,
+    mut balls_lost: EventReader<BallLost>,
+    mut game_data: ResMut<GameData>,
+    mut app_state: ResMut<State<GameState>>,
+) {
+    if balls_lost.iter().next().is_none() || game_config.mode == GameMode::Versus {
+        return;
+    }
+
+    game_data.lives = game_data.lives.saturating_sub(1);
+    if game_data.lives == 0 {
+        // the time running out or a mode's own ending in the same frame may have ended the game
+        // already
+        let _ = app_state.set(GameState::GameOver);
+    }
 }
@@ -0,0 +1,293 @@
+use bevy::prelude::*;
+
+use crate::{
+    high_scores::{self, HighScores},
+    scaling::ScaledFont,
+    GameState,
+};
+
+use super::{
+    clock::GameClock,
+    components::{GameConfig, GameData, GameEntity},
+    game_mode::GameMode,
+    game_state::{advance_level, lose_life},
+    scoring::{Combo, ScoreUpdate},
+    time_attack::{update_clock, TimeAttack},
+    versus::Versus,
+};
+
+const HUD_FONT_SIZE: f32 = 24.0;
+const LABEL_COLOR: Color = Color::rgb(0.6, 0.6, 0.6);
+const VALUE_COLOR: Color = Color::rgb(1.0, 0.5, 0.5);
+// Distance from the window's edges, the arena leaves a strip free on either side
+const HUD_MARGIN: f32 = 10.0;
+
+// Score, best score, level and lives, left of the arena
+#[derive(Component)]
+struct StatsPanel;
+
+// Play time (or the time left in time attack), difficulty and combo, right of the arena
+#[derive(Component)]
+struct RallyPanel;
+
+// Score and lives of one of the players in versus, on their side of the arena
+#[derive(Component)]
+struct PlayerPanel(usize);
+
+// Indices of the values in the panels' text sections, every value follows its label
+const SCORE_SECTION: usize = 1;
+const BEST_SECTION: usize = 3;
+const LEVEL_SECTION: usize = 5;
+const LIVES_SECTION: usize = 7;
+const TIME_SECTION: usize = 1;
+const COMBO_LABEL_SECTION: usize = 4;
+const COMBO_SECTION: usize = 5;
+const PLAYER_SCORE_SECTION: usize = 1;
+const PLAYER_LIVES_SECTION: usize = 3;
+
+// Time spent playing this game, without pauses
+#[derive(Default)]
+pub struct PlayTime {
+    pub elapsed: f32,
+}
+
+// Game stats shown on both sides of the arena. Every part only updates when what it shows
+// has changed.
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PlayTime>()
+            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(spawn_hud))
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(update_play_time)
+                    .with_system(
+                        update_stats
+                            .after(ScoreUpdate)
+                            .after(advance_level)
+                            .after(lose_life),
+                    )
+                    .with_system(
+                        update_play_time_text
+                            .after(update_play_time)
+                            .after(update_clock),
+                    )
+                    .with_system(update_combo.after(ScoreUpdate))
+                    .with_system(update_player_panels.after(ScoreUpdate).after(lose_life)),
+            );
+    }
+}
+
+fn spawn_hud(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_config: Res<GameConfig>,
+    mut play_time: ResMut<PlayTime>,
+) {
+    *play_time = PlayTime::default();
+
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let label = |value: &str| TextSection {
+        value: value.to_string(),
+        style: TextStyle {
+            font: font.clone(),
+            font_size: HUD_FONT_SIZE,
+            color: LABEL_COLOR,
+        },
+    };
+    let value = |value: &str| TextSection {
+        value: value.to_string(),
+        style: TextStyle {
+            font: font.clone(),
+            font_size: HUD_FONT_SIZE,
+            color: VALUE_COLOR,
+        },
+    };
+
+    let panel_style = |left| Style {
+        position_type: PositionType::Absolute,
+        position: Rect {
+            top: Val::Px(HUD_MARGIN),
+            left: if left {
+                Val::Px(HUD_MARGIN)
+            } else {
+                Val::Undefined
+            },
+            right: if left {
+                Val::Undefined
+            } else {
+                Val::Px(HUD_MARGIN)
+            },
+            ..default()
+        },
+        ..default()
+    };
+    let alignment = |left| TextAlignment {
+        horizontal: if left {
+            HorizontalAlign::Left
+        } else {
+            HorizontalAlign::Right
+        },
+        ..default()
+    };
+
+    // in versus each side of the arena belongs to one of the players
+    if game_config.mode == GameMode::Versus {
+        for player in 0..2 {
+            let left = player == 0;
+            commands
+                .spawn_bundle(TextBundle {
+                    style: panel_style(left),
+                    text: Text {
+                        sections: vec![
+                            label(&format!("Player {}\n\nScore\n", player + 1)),
+                            value(""),
+                            label("\n\nLives\n"),
+                            value(""),
+                        ],
+                        alignment: alignment(left),
+                    },
+                    ..default()
+                })
+                .insert(PlayerPanel(player))
+                .insert(ScaledFont(HUD_FONT_SIZE))
+                .insert(GameEntity);
+        }
+        return;
+    }
+
+    // the values are filled in by the update systems on the first frame
+    commands
+        .spawn_bundle(TextBundle {
+            style: panel_style(true),
+            text: Text {
+                sections: vec![
+                    label("Score\n"),
+                    value(""),
+                    label("\n\nBest\n"),
+                    value(""),
+                    label("\n\nLevel\n"),
+                    value(""),
+                    label("\n\nLives\n"),
+                    value(""),
+                ],
+                ..default()
+            },
+            ..default()
+        })
+        .insert(StatsPanel)
+        .insert(ScaledFont(HUD_FONT_SIZE))
+        .insert(GameEntity);
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: panel_style(false),
+            text: Text {
+                sections: vec![
+                    label(if game_config.mode == GameMode::TimeAttack {
+                        "Time left\n"
+                    } else {
+                        "Time\n"
+                    }),
+                    value("0:00"),
+                    label("\n\n"),
+                    value(&high_scores::table_name(
+                        game_config.mode,
+                        game_config.difficulty,
+                    )),
+                    label(""),
+                    value(""),
+                ],
+                alignment: alignment(false),
+            },
+            ..default()
+        })
+        .insert(RallyPanel)
+        .insert(ScaledFont(HUD_FONT_SIZE))
+        .insert(GameEntity);
+}
+
+fn update_play_time(clock: Res<GameClock>, mut play_time: ResMut<PlayTime>) {
+    play_time.elapsed += clock.delta_seconds();
+}
+
+fn update_stats(
+    game_config: Res<GameConfig>,
+    game_data: Res<GameData>,
+    high_scores: Option<Res<HighScores>>,
+    mut panel_query: Query<&mut Text, With<StatsPanel>>,
+    added_query: Query<(), Added<StatsPanel>>,
+) {
+    if !game_data.is_changed() && added_query.is_empty() {
+        return;
+    }
+
+    // the current game counts as the best as soon as it beats the saved scores
+    let best = high_scores
+        .as_ref()
+        .and_then(|high_scores| high_scores.best(game_config.mode, game_config.difficulty))
+        .map_or(0, |high_score| high_score.score)
+        .max(game_data.score);
+
+    for mut text in panel_query.iter_mut() {
+        text.sections[SCORE_SECTION].value = game_data.score.to_string();
+        text.sections[BEST_SECTION].value = best.to_string();
+        text.sections[LEVEL_SECTION].value = game_data.level.to_string();
+        text.sections[LIVES_SECTION].value = game_data.lives.to_string();
+    }
+}
+
+// Only touches the text when the shown second changes
+fn update_play_time_text(
+    game_config: Res<GameConfig>,
+    play_time: Res<PlayTime>,
+    time_attack: Res<TimeAttack>,
+    mut panel_query: Query<&mut Text, With<RallyPanel>>,
+) {
+    let seconds = if game_config.mode == GameMode::TimeAttack {
+        time_attack.time_left.ceil() as u32
+    } else {
+        play_time.elapsed as u32
+    };
+    let time = format!("{}:{:02}", seconds / 60, seconds % 60);
+
+    for mut text in panel_query.iter_mut() {
+        if text.sections[TIME_SECTION].value != time {
+            text.sections[TIME_SECTION].value = time.clone();
+        }
+    }
+}
+
+fn update_player_panels(
+    versus: Res<Versus>,
+    mut panel_query: Query<(&mut Text, &PlayerPanel)>,
+    added_query: Query<(), Added<PlayerPanel>>,
+) {
+    if !versus.is_changed() && added_query.is_empty() {
+        return;
+    }
+
+    for (mut text, PlayerPanel(player)) in panel_query.iter_mut() {
+        let player = &versus.players[*player];
+        text.sections[PLAYER_SCORE_SECTION].value = player.score.to_string();
+        text.sections[PLAYER_LIVES_SECTION].value = player.lives.to_string();
+    }
+}
+
+fn update_combo(combo: Res<Combo>, mut panel_query: Query<&mut Text, With<RallyPanel>>) {
+    if !combo.is_changed() {
+        return;
+    }
+
+    for mut text in panel_query.iter_mut() {
+        // only worth showing once it adds something
+        if combo.multiplier() > 1 {
+            text.sections[COMBO_LABEL_SECTION].value = "\n\nCombo\n".to_string();
+            text.sections[COMBO_SECTION].value = format!("{} x{}", combo.count, combo.multiplier());
+        } else if !text.sections[COMBO_SECTION].value.is_empty() {
+            text.sections[COMBO_LABEL_SECTION].value.clear();
+            text.sections[COMBO_SECTION].value.clear();
+        }
+    }
+}
@@ -0,0 +1,357 @@
+use std::path::Path;
+
+use clap::ValueEnum;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use super::{
+    bricks::{BrickGrid, BrickKind},
+    level_layout::LevelLayout,
+};
+
+// Spelled out one letter per level by the text pattern when no other text is given
+const DEFAULT_TEXT: &str = "BREAKOUT";
+// Passes of smoothing that turn random noise into caves
+const CAVE_SMOOTHING_PASSES: usize = 2;
+
+// The shapes the generator can lay bricks out in
+#[derive(Debug, Clone, Copy, Eq, PartialEq, ValueEnum)]
+pub enum Pattern {
+    // random bricks, mirrored left to right
+    Symmetric,
+    // a pyramid, upside down pyramid or diamond
+    Pyramid,
+    Checkerboard,
+    // noise smoothed into caves hanging from the ceiling
+    Caves,
+    // a letter of the generator's text
+    Text,
+    // the shape of the generator's image
+    Image,
+}
+
+// How full and how tough generated levels are
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GeneratorDifficulty {
+    // how much of the grid the random patterns (symmetric and caves) fill, from 0 to 1
+    pub density: f32,
+    // fraction of the bricks that are strong
+    pub strong: f32,
+    // fraction of the bricks that are steel
+    pub steel: f32,
+}
+
+impl GeneratorDifficulty {
+    // Levels get fuller and tougher as the game goes on
+    pub fn for_level(level: u32) -> Self {
+        let level = level.max(1) as f32;
+        GeneratorDifficulty {
+            density: (0.5 + 0.05 * level).min(0.9),
+            strong: (0.1 * (level - 1.0)).min(0.5),
+            steel: (0.04 * (level - 2.0)).clamp(0.0, 0.2),
+        }
+    }
+}
+
+// Makes level layouts from a seed, so a game with the same seed gets the same levels
+pub struct LevelGenerator {
+    // a random pattern is picked for every level when there is none
+    pub pattern: Option<Pattern>,
+    // overrides the difficulty that comes with the level number
+    pub difficulty: Option<GeneratorDifficulty>,
+    pub text: String,
+    // which cells of the grid the image pattern fills, rows from the top
+    pub image: Option<Vec<Vec<bool>>>,
+}
+
+impl Default for LevelGenerator {
+    fn default() -> Self {
+        LevelGenerator {
+            pattern: None,
+            difficulty: None,
+            text: DEFAULT_TEXT.to_string(),
+            image: None,
+        }
+    }
+}
+
+impl LevelGenerator {
+    // The image is scaled down to the brick grid. In images with transparency the opaque pixels
+    // become bricks, in others the ones darker than mid grey.
+    pub fn load_image(path: &Path, grid: &BrickGrid) -> Result<Vec<Vec<bool>>, String> {
+        let image = image::open(path).map_err(|err| err.to_string())?;
+        let image = image::imageops::resize(
+            &image.to_luma_alpha8(),
+            grid.n_columns as u32,
+            grid.n_rows as u32,
+            image::imageops::FilterType::Triangle,
+        );
+        let transparent = image.pixels().any(|pixel| pixel[1] < 128);
+
+        Ok((0..grid.n_rows)
+            .map(|row| {
+                (0..grid.n_columns)
+                    .map(|column| {
+                        let pixel = image.get_pixel(column as u32, row as u32);
+                        if transparent {
+                            pixel[1] >= 128
+                        } else {
+                            pixel[0] < 128
+                        }
+                    })
+                    .collect()
+            })
+            .collect())
+    }
+
+    pub fn generate(&self, seed: u64, level: u32, grid: &BrickGrid) -> LevelLayout {
+        // every level gets its own generator, so levels don't depend on the ones before them
+        let mut rng =
+            StdRng::seed_from_u64(seed ^ (level as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        let difficulty = self
+            .difficulty
+            .unwrap_or_else(|| GeneratorDifficulty::for_level(level));
+
+        let mut patterns = vec![
+            Pattern::Symmetric,
+            Pattern::Pyramid,
+            Pattern::Checkerboard,
+            Pattern::Caves,
+            Pattern::Text,
+        ];
+        if self.image.is_some() {
+            patterns.push(Pattern::Image);
+        }
+        let pattern = self
+            .pattern
+            .unwrap_or_else(|| *patterns.choose(&mut rng).unwrap());
+
+        let mut shape = match pattern {
+            Pattern::Symmetric => symmetric(&mut rng, grid, difficulty.density),
+            Pattern::Pyramid => pyramid(&mut rng, grid),
+            Pattern::Checkerboard => checkerboard(&mut rng, grid),
+            Pattern::Caves => caves(&mut rng, grid, difficulty.density),
+            Pattern::Text => text(grid, &self.text, level),
+            Pattern::Image => self
+                .image
+                .clone()
+                .unwrap_or_else(|| symmetric(&mut rng, grid, difficulty.density)),
+        };
+
+        // a level has to have something to break
+        if !shape.iter().flatten().any(|filled| *filled) {
+            shape[0][grid.n_columns / 2] = true;
+        }
+
+        let mut layout = LevelLayout::empty(grid);
+        // bricks mirrored across the middle get the same kind, which keeps symmetric shapes
+        // looking symmetric
+        for (row, cells) in shape.iter().enumerate() {
+            for column in 0..grid.n_columns.div_ceil(2) {
+                let kind = random_kind(&mut rng, &difficulty);
+                for column in [column, grid.n_columns - 1 - column] {
+                    if cells[column] {
+                        layout.set(row, column, Some(kind));
+                    }
+                }
+            }
+        }
+
+        if !layout.has_breakable_bricks() {
+            let (row, column) = (0..grid.n_rows)
+                .flat_map(|row| (0..grid.n_columns).map(move |column| (row, column)))
+                .find(|(row, column)| shape[*row][*column])
+                .unwrap();
+            layout.set(row, column, Some(BrickKind::Normal));
+        }
+        layout
+    }
+}
+
+fn random_kind(rng: &mut StdRng, difficulty: &GeneratorDifficulty) -> BrickKind {
+    let roll: f32 = rng.gen();
+    if roll < difficulty.steel {
+        BrickKind::Steel
+    } else if roll < difficulty.steel + difficulty.strong {
+        BrickKind::Strong
+    } else {
+        BrickKind::Normal
+    }
+}
+
+fn empty_shape(grid: &BrickGrid) -> Vec<Vec<bool>> {
+    vec![vec![false; grid.n_columns]; grid.n_rows]
+}
+
+fn symmetric(rng: &mut StdRng, grid: &BrickGrid, density: f32) -> Vec<Vec<bool>> {
+    let mut shape = empty_shape(grid);
+    for row in shape.iter_mut() {
+        for column in 0..grid.n_columns.div_ceil(2) {
+            let filled = rng.gen::<f32>() < density;
+            row[column] = filled;
+            row[grid.n_columns - 1 - column] = filled;
+        }
+    }
+    shape
+}
+
+fn pyramid(rng: &mut StdRng, grid: &BrickGrid) -> Vec<Vec<bool>> {
+    let center = (grid.n_columns - 1) as f32 / 2.0;
+    let variant = rng.gen_range(0..3);
+
+    let mut shape = empty_shape(grid);
+    for (row, cells) in shape.iter_mut().enumerate() {
+        // how far down the shape this row is, from 0 to 1
+        let depth = (row + 1) as f32 / grid.n_rows as f32;
+        let width = match variant {
+            0 => depth,
+            1 => 1.0 - depth + 1.0 / grid.n_rows as f32,
+            _ => 1.0 - (2.0 * depth - 1.0).abs() + 1.0 / grid.n_rows as f32,
+        };
+        let half_width = width * (center + 0.5);
+        for (column, cell) in cells.iter_mut().enumerate() {
+            *cell = (column as f32 - center).abs() < half_width;
+        }
+    }
+    shape
+}
+
+fn checkerboard(rng: &mut StdRng, grid: &BrickGrid) -> Vec<Vec<bool>> {
+    let block_height = rng.gen_range(1..=2);
+    let phase = rng.gen_range(0..2);
+
+    let mut shape = empty_shape(grid);
+    for (row, cells) in shape.iter_mut().enumerate() {
+        for (column, cell) in cells.iter_mut().enumerate() {
+            *cell = (row / block_height + column + phase) % 2 == 0;
+        }
+    }
+    shape
+}
+
+fn caves(rng: &mut StdRng, grid: &BrickGrid, density: f32) -> Vec<Vec<bool>> {
+    let mut shape = empty_shape(grid);
+    for cell in shape.iter_mut().flatten() {
+        *cell = rng.gen::<f32>() < density;
+    }
+
+    // every cell takes on what most of its neighbours are, the ceiling and walls count as filled
+    // so the caves hang from them
+    for _ in 0..CAVE_SMOOTHING_PASSES {
+        let previous = shape.clone();
+        for (row, cells) in shape.iter_mut().enumerate() {
+            for (column, cell) in cells.iter_mut().enumerate() {
+                let mut filled_neighbours = 0;
+                for (row_step, column_step) in [
+                    (-1, -1),
+                    (-1, 0),
+                    (-1, 1),
+                    (0, -1),
+                    (0, 1),
+                    (1, -1),
+                    (1, 0),
+                    (1, 1),
+                ] {
+                    let neighbour_row = row as isize + row_step;
+                    let neighbour_column = column as isize + column_step;
+                    let filled = if neighbour_row < 0
+                        || neighbour_column < 0
+                        || neighbour_column >= grid.n_columns as isize
+                    {
+                        true
+                    } else if neighbour_row >= grid.n_rows as isize {
+                        false
+                    } else {
+                        previous[neighbour_row as usize][neighbour_column as usize]
+                    };
+                    if filled {
+                        filled_neighbours += 1;
+                    }
+                }
+
+                if filled_neighbours >= 5 {
+                    *cell = true;
+                } else if filled_neighbours <= 3 {
+                    *cell = false;
+                }
+            }
+        }
+    }
+    shape
+}
+
+// One letter per level, centered in the grid
+fn text(grid: &BrickGrid, text: &str, level: u32) -> Vec<Vec<bool>> {
+    let glyphs: Vec<&[&str; GLYPH_HEIGHT]> = text
+        .chars()
+        .filter_map(|letter| glyph(letter.to_ascii_uppercase()))
+        .collect();
+
+    let mut shape = empty_shape(grid);
+    if glyphs.is_empty() {
+        return shape;
+    }
+
+    let glyph = glyphs[(level.max(1) - 1) as usize % glyphs.len()];
+    let top = grid.n_rows.saturating_sub(GLYPH_HEIGHT) / 2;
+    let left = grid.n_columns.saturating_sub(GLYPH_WIDTH) / 2;
+    for (glyph_row, symbols) in glyph.iter().enumerate() {
+        for (glyph_column, symbol) in symbols.chars().enumerate() {
+            let (row, column) = (top + glyph_row, left + glyph_column);
+            if row < grid.n_rows && column < grid.n_columns {
+                shape[row][column] = symbol == '#';
+            }
+        }
+    }
+    shape
+}
+
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+
+fn glyph(letter: char) -> Option<&'static [&'static str; GLYPH_HEIGHT]> {
+    GLYPHS
+        .iter()
+        .find(|(glyph_letter, _)| *glyph_letter == letter)
+        .map(|(_, glyph)| glyph)
+}
+
+// A 5x7 font, which happens to be the size of the brick grid
+#[rustfmt::skip]
+const GLYPHS: [(char, [&str; GLYPH_HEIGHT]); 36] = [
+    ('A', [".###.", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"]),
+    ('B', ["####.", "#...#", "#...#", "####.", "#...#", "#...#", "####."]),
+    ('C', [".###.", "#...#", "#....", "#....", "#....", "#...#", ".###."]),
+    ('D', ["####.", "#...#", "#...#", "#...#", "#...#", "#...#", "####."]),
+    ('E', ["#####", "#....", "#....", "####.", "#....", "#....", "#####"]),
+    ('F', ["#####", "#....", "#....", "####.", "#....", "#....", "#...."]),
+    ('G', [".###.", "#...#", "#....", "#.###", "#...#", "#...#", ".###."]),
+    ('H', ["#...#", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"]),
+    ('I', [".###.", "..#..", "..#..", "..#..", "..#..", "..#..", ".###."]),
+    ('J', ["..###", "...#.", "...#.", "...#.", "...#.", "#..#.", ".##.."]),
+    ('K', ["#...#", "#..#.", "#.#..", "##...", "#.#..", "#..#.", "#...#"]),
+    ('L', ["#....", "#....", "#....", "#....", "#....", "#....", "#####"]),
+    ('M', ["#...#", "##.##", "#.#.#", "#.#.#", "#...#", "#...#", "#...#"]),
+    ('N', ["#...#", "#...#", "##..#", "#.#.#", "#..##", "#...#", "#...#"]),
+    ('O', [".###.", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."]),
+    ('P', ["####.", "#...#", "#...#", "####.", "#....", "#....", "#...."]),
+    ('Q', [".###.", "#...#", "#...#", "#...#", "#.#.#", "#..#.", ".##.#"]),
+    ('R', ["####.", "#...#", "#...#", "####.", "#.#..", "#..#.", "#...#"]),
+    ('S', [".####", "#....", "#....", ".###.", "....#", "....#", "####."]),
+    ('T', ["#####", "..#..", "..#..", "..#..", "..#..", "..#..", "..#.."]),
+    ('U', ["#...#", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."]),
+    ('V', ["#...#", "#...#", "#...#", "#...#", "#...#", ".#.#.", "..#.."]),
+    ('W', ["#...#", "#...#", "#...#", "#.#.#", "#.#.#", "#.#.#", ".#.#."]),
+    ('X', ["#...#", "#...#", ".#.#.", "..#..", ".#.#.", "#...#", "#...#"]),
+    ('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#..", "..#.."]),
+    ('Z', ["#####", "....#", "...#.", "..#..", ".#...", "#....", "#####"]),
+    ('0', [".###.", "#...#", "#..##", "#.#.#", "##..#", "#...#", ".###."]),
+    ('1', ["..#..", ".##..", "..#..", "..#..", "..#..", "..#..", ".###."]),
+    ('2', [".###.", "#...#", "....#", "...#.", "..#..", ".#...", "#####"]),
+    ('3', ["#####", "...#.", "..#..", "...#.", "....#", "#...#", ".###."]),
+    ('4', ["...#.", "..##.", ".#.#.", "#..#.", "#####", "...#.", "...#."]),
+    ('5', ["#####", "#....", "####.", "....#", "....#", "#...#", ".###."]),
+    ('6', ["..##.", ".#...", "#....", "####.", "#...#", "#...#", ".###."]),
+    ('7', ["#####", "....#", "...#.", "..#..", ".#...", ".#...", ".#..."]),
+    ('8', [".###.", "#...#", "#...#", ".###.", "#...#", "#...#", ".###."]),
+    ('9', [".###.", "#...#", "#...#", ".####", "....#", "...#.", ".##.."]),
+];
@@ -0,0 +1,201 @@
+use std::{fs, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use super::bricks::{BrickGrid, BrickKind};
+
+const EMPTY_SYMBOL: char = '.';
+
+// The bricks of a level, as saved by the level editor. Every row is a string, from the top of the
+// arena down, with one character per column: `.` for no brick, `#` for a normal brick, `S` for a
+// strong one, `X` for steel and `T` for a time brick.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LevelLayout {
+    pub rows: Vec<String>,
+}
+
+impl LevelLayout {
+    pub fn empty(grid: &BrickGrid) -> Self {
+        LevelLayout {
+            rows: vec![EMPTY_SYMBOL.to_string().repeat(grid.n_columns); grid.n_rows],
+        }
+    }
+
+    // The built-in layout of a level, later levels mix in stronger bricks starting from the top
+    pub fn generated(level: u32, grid: &BrickGrid) -> Self {
+        let mut layout = LevelLayout::empty(grid);
+        for row in 0..grid.n_rows {
+            for column in 0..grid.n_columns {
+                let row_from_bottom = grid.n_rows - 1 - row;
+                let kind = if level >= 3 && row_from_bottom == grid.n_rows / 2 && column % 2 == 1 {
+                    BrickKind::Steel
+                } else if (row as u32) < level - 1 {
+                    BrickKind::Strong
+                } else {
+                    BrickKind::Normal
+                };
+                layout.set(row, column, Some(kind));
+            }
+        }
+        layout
+    }
+
+    // Files can have fewer rows and columns than the grid, but not more
+    pub fn load(path: &Path, grid: &BrickGrid) -> Result<Self, String> {
+        let layout: LevelLayout = fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| ron::from_str(&contents).map_err(|err| err.to_string()))?;
+        layout.validate(grid)?;
+        Ok(layout)
+    }
+
+    // Checks a layout from outside the game fits the grid and only has known bricks
+    pub fn validate(&self, grid: &BrickGrid) -> Result<(), String> {
+        if self.rows.len() > grid.n_rows {
+            return Err(format!(
+                "{} rows, the grid only has {}",
+                self.rows.len(),
+                grid.n_rows
+            ));
+        }
+        if let Some((index, row)) = self
+            .rows
+            .iter()
+            .enumerate()
+            .find(|(_, row)| row.chars().count() > grid.n_columns)
+        {
+            return Err(format!(
+                "row {} is {} cells wide, the grid only has {} columns",
+                index + 1,
+                row.chars().count(),
+                grid.n_columns
+            ));
+        }
+
+        if let Some(symbol) = self
+            .rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .find(|symbol| *symbol != EMPTY_SYMBOL && kind_from_symbol(*symbol).is_none())
+        {
+            return Err(format!("unknown brick '{}'", symbol));
+        }
+        Ok(())
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())
+            .and_then(|contents| fs::write(path, contents).map_err(|err| err.to_string()))
+    }
+
+    // Anything outside the rows in the file is empty
+    pub fn get(&self, row: usize, column: usize) -> Option<BrickKind> {
+        self.rows
+            .get(row)
+            .and_then(|symbols| symbols.chars().nth(column))
+            .and_then(kind_from_symbol)
+    }
+
+    pub fn set(&mut self, row: usize, column: usize, kind: Option<BrickKind>) {
+        if self.rows.len() <= row {
+            self.rows.resize(row + 1, String::new());
+        }
+
+        let mut symbols: Vec<char> = self.rows[row].chars().collect();
+        if symbols.len() <= column {
+            symbols.resize(column + 1, EMPTY_SYMBOL);
+        }
+        symbols[column] = kind.map_or(EMPTY_SYMBOL, symbol);
+        self.rows[row] = symbols.into_iter().collect();
+    }
+
+    // Only breakable bricks have to go for the level to be cleared
+    pub fn has_breakable_bricks(&self) -> bool {
+        self.rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .filter_map(kind_from_symbol)
+            .any(|kind| kind.is_breakable())
+    }
+}
+
+fn symbol(kind: BrickKind) -> char {
+    match kind {
+        BrickKind::Normal => '#',
+        BrickKind::Strong => 'S',
+        BrickKind::Steel => 'X',
+        BrickKind::Time => 'T',
+    }
+}
+
+fn kind_from_symbol(symbol: char) -> Option<BrickKind> {
+    match symbol {
+        '#' => Some(BrickKind::Normal),
+        'S' => Some(BrickKind::Strong),
+        'X' => Some(BrickKind::Steel),
+        'T' => Some(BrickKind::Time),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    // Saves the rows to a file of their own and loads them back
+    fn load_rows(name: &str, rows: &[&str]) -> Result<LevelLayout, String> {
+        let path = env::temp_dir().join(format!("breakout-{}-{}.ron", std::process::id(), name));
+        let layout = LevelLayout {
+            rows: rows.iter().map(|row| row.to_string()).collect(),
+        };
+        layout.save(&path).unwrap();
+        let loaded = LevelLayout::load(&path, &BrickGrid::default());
+        let _ = fs::remove_file(&path);
+        loaded
+    }
+
+    #[test]
+    fn layouts_that_fit_load() {
+        let grid = BrickGrid::default();
+        let full_row = "#".repeat(grid.n_columns);
+        let mut rows = vec![full_row.as_str(); grid.n_rows];
+        rows[0] = "SX.T";
+        let layout = load_rows("fit", &rows).unwrap();
+        assert_eq!(layout.get(0, 1), Some(BrickKind::Steel));
+        assert_eq!(layout.get(0, 2), None);
+        assert_eq!(
+            layout.get(grid.n_rows - 1, grid.n_columns - 1),
+            Some(BrickKind::Normal)
+        );
+    }
+
+    #[test]
+    fn too_many_rows_are_rejected() {
+        let rows = vec!["#"; BrickGrid::default().n_rows + 1];
+        assert!(load_rows("tall", &rows).is_err());
+    }
+
+    #[test]
+    fn too_wide_rows_are_rejected() {
+        let wide_row = "#".repeat(BrickGrid::default().n_columns + 1);
+        assert!(load_rows("wide", &["#", &wide_row]).is_err());
+    }
+
+    #[test]
+    fn unknown_bricks_are_rejected() {
+        assert!(load_rows("unknown", &["#?#"]).is_err());
+    }
+
+    #[test]
+    fn steel_alone_can_not_be_cleared() {
+        assert!(!load_rows("steel", &["X.X", "..."])
+            .unwrap()
+            .has_breakable_bricks());
+        assert!(load_rows("strong", &["X.S"])
+            .unwrap()
+            .has_breakable_bricks());
+    }
+}
@@ -1,49 +1,70 @@
 use bevy::prelude::*;
 
 use crate::{
+    high_scores::{self, HighScores, RecordHighScore},
+    scaling::{ScaledFont, ScaledSize},
     utilities::{
-        despawn_entities, TEXT_COLOR, SELECTED_BUTTON,
+        confirm_pressed, despawn_entities, MenuEvent, MenuStyle, SELECTED_BUTTON, TEXT_COLOR,
     },
     GameState,
 };
 
-use super::components::GameData;
+use super::{
+    components::{GameConfig, GameData},
+    editor_state::PlayTest,
+    game_mode::GameMode,
+    hud::PlayTime,
+    time_attack::TimeAttack,
+    versus::Versus,
+};
 
 #[derive(Component)]
 enum LoseMenuButtonAction {
-    Okay
+    Okay,
 }
 
 #[derive(Component)]
 struct LoseMenuEntity;
 
+const SCORE_FONT_SIZE: f32 = 80.0;
+const BEST_SCORE_FONT_SIZE: f32 = 40.0;
+const STATS_FONT_SIZE: f32 = 28.0;
+
 pub struct LosePlugin;
 
 impl Plugin for LosePlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(render_lose_menu))
-            .add_system_set(
-                SystemSet::on_update(GameState::GameOver)
-                    .with_system(select_menu_item),
-            )
-            .add_system_set(
-                SystemSet::on_exit(GameState::GameOver).with_system(despawn_entities::<LoseMenuEntity>),
-            );
+        app.add_system_set(
+            SystemSet::on_enter(GameState::GameOver)
+                .with_system(render_lose_menu.after(RecordHighScore)),
+        )
+        .add_system_set(SystemSet::on_update(GameState::GameOver).with_system(select_menu_item))
+        .add_system_set(
+            SystemSet::on_exit(GameState::GameOver).with_system(despawn_entities::<LoseMenuEntity>),
+        );
     }
 }
 
-fn render_lose_menu(mut commands: Commands, asset_server: Res<AssetServer>, windows: Res<Windows>, game_data: Res<GameData>) {
-    let window = windows.get_primary().unwrap();
-    let window_height = window.height();
-    let window_width = window.width();
+#[allow(clippy::too_many_arguments)]
+fn render_lose_menu(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    game_config: Res<GameConfig>,
+    game_data: Res<GameData>,
+    high_scores: Option<Res<HighScores>>,
+    play_time: Res<PlayTime>,
+    time_attack: Res<TimeAttack>,
+    versus: Res<Versus>,
+) {
     let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let menu_style = MenuStyle::LARGE;
 
     let button_style = Style {
         size: Size::new(
-            Val::Px((window_width / 2.0).round()),
-            Val::Px((window_height / 5.0).round()),
+            Val::Px(menu_style.button_size.x),
+            Val::Px(menu_style.button_size.y),
         ),
-        margin: Rect::all(Val::Px(20.0)),
+        margin: Rect::all(Val::Px(menu_style.button_margin)),
         justify_content: JustifyContent::Center,
         align_items: AlignItems::Center,
         ..default()
@@ -51,10 +72,29 @@ fn render_lose_menu(mut commands: Commands, asset_server: Res<AssetServer>, wind
 
     let button_text_style = TextStyle {
         font: font.clone(),
-        font_size: (window_height / 5.5).round(),
+        font_size: menu_style.button_font_size,
         color: TEXT_COLOR,
     };
 
+    // versus is about who won, with both scores side by side
+    let (title, score) = match game_config.mode {
+        GameMode::Versus => (
+            match versus.winner() {
+                Some(player) => format!("Player {} Wins", player + 1),
+                None => "Game Over".to_string(),
+            },
+            format!("{} - {}", versus.players[0].score, versus.players[1].score),
+        ),
+        GameMode::TimeAttack if time_attack.is_over() => (
+            "Time's Up".to_string(),
+            format!("Your score: {}", game_data.score),
+        ),
+        _ => (
+            "Game Over".to_string(),
+            format!("Your score: {}", game_data.score),
+        ),
+    };
+
     commands
         .spawn_bundle(UiCameraBundle::default())
         .insert(LoseMenuEntity);
@@ -72,39 +112,106 @@ fn render_lose_menu(mut commands: Commands, asset_server: Res<AssetServer>, wind
         })
         .insert(LoseMenuEntity)
         .with_children(|parent| {
-            parent.spawn_bundle(TextBundle {
-                style: Style {
-                    margin: Rect::all(Val::Px(30.0)),
-                    ..default()
-                },
-                text: Text::with_section(
-                    "Game Over",
-                    TextStyle {
-                        font: font.clone(),
-                        font_size: (window_height / 4.0).round(),
-                        color: TEXT_COLOR,
+            parent
+                .spawn_bundle(TextBundle {
+                    style: Style {
+                        margin: Rect::all(Val::Px(30.0)),
+                        ..default()
                     },
-                    Default::default(),
-                ),
-                ..default()
-            }).insert(LoseMenuEntity);
-
-            parent.spawn_bundle(TextBundle {
-                style: Style {
-                    margin: Rect::all(Val::Px(5.0)),
+                    text: Text::with_section(
+                        title,
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: menu_style.title_font_size,
+                            color: TEXT_COLOR,
+                        },
+                        Default::default(),
+                    ),
                     ..default()
-                },
-                text: Text::with_section(
-                    format!("Your score: {}", game_data.score),
-                    TextStyle {
-                        font: font.clone(),
-                        font_size: (window_height / 6.0).round(),
-                        color: TEXT_COLOR,
+                })
+                .insert(LoseMenuEntity)
+                .insert(ScaledFont(menu_style.title_font_size));
+
+            parent
+                .spawn_bundle(TextBundle {
+                    style: Style {
+                        margin: Rect::all(Val::Px(5.0)),
+                        ..default()
                     },
-                    Default::default(),
-                ),
-                ..default()
-            });
+                    text: Text::with_section(
+                        score,
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: SCORE_FONT_SIZE,
+                            color: TEXT_COLOR,
+                        },
+                        Default::default(),
+                    ),
+                    ..default()
+                })
+                .insert(ScaledFont(SCORE_FONT_SIZE));
+
+            // the game was already recorded, so this can be the score that was just made
+            if let Some(best) = high_scores
+                .as_ref()
+                .and_then(|high_scores| high_scores.best(game_config.mode, game_config.difficulty))
+            {
+                parent
+                    .spawn_bundle(TextBundle {
+                        style: Style {
+                            margin: Rect::all(Val::Px(5.0)),
+                            ..default()
+                        },
+                        text: Text::with_section(
+                            format!(
+                                "Best on {}: {}",
+                                high_scores::table_name(game_config.mode, game_config.difficulty),
+                                best.score
+                            ),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: BEST_SCORE_FONT_SIZE,
+                                color: TEXT_COLOR,
+                            },
+                            Default::default(),
+                        ),
+                        ..default()
+                    })
+                    .insert(ScaledFont(BEST_SCORE_FONT_SIZE));
+            }
+
+            if game_config.mode == GameMode::TimeAttack {
+                let seconds = play_time.elapsed as u32;
+                parent
+                    .spawn_bundle(TextBundle {
+                        style: Style {
+                            margin: Rect::all(Val::Px(5.0)),
+                            ..default()
+                        },
+                        text: Text::with_section(
+                            format!(
+                                "{} bricks and {} levels cleared in {}:{:02}\n\
+                                 {:.0} seconds won from time bricks",
+                                time_attack.bricks_broken,
+                                time_attack.levels_cleared,
+                                seconds / 60,
+                                seconds % 60,
+                                time_attack.bonus_time
+                            ),
+                            TextStyle {
+                                font: font.clone(),
+                                font_size: STATS_FONT_SIZE,
+                                color: TEXT_COLOR,
+                            },
+                            TextAlignment {
+                                horizontal: HorizontalAlign::Center,
+                                ..default()
+                            },
+                        ),
+                        ..default()
+                    })
+                    .insert(ScaledFont(STATS_FONT_SIZE));
+            }
 
             parent
                 .spawn_bundle(ButtonBundle {
@@ -113,11 +220,14 @@ fn render_lose_menu(mut commands: Commands, asset_server: Res<AssetServer>, wind
                     ..default()
                 })
                 .insert(LoseMenuButtonAction::Okay)
+                .insert(ScaledSize(menu_style.button_size))
                 .with_children(|parent| {
-                    parent.spawn_bundle(TextBundle {
-                        text: Text::with_section("Okay", button_text_style, Default::default()),
-                        ..default()
-                    });
+                    parent
+                        .spawn_bundle(TextBundle {
+                            text: Text::with_section("Okay", button_text_style, Default::default()),
+                            ..default()
+                        })
+                        .insert(ScaledFont(menu_style.button_font_size));
                 });
         });
 }
@@ -126,13 +236,22 @@ fn select_menu_item(
     mut keyboard_input: ResMut<Input<KeyCode>>,
     selected_option_query: Query<&LoseMenuButtonAction>,
     mut app_state: ResMut<State<GameState>>,
+    mut menu_events: EventWriter<MenuEvent>,
+    play_test: Option<Res<PlayTest>>,
 ) {
     let menu_action = selected_option_query.single();
 
-    if keyboard_input.just_pressed(KeyCode::Return) {
+    if confirm_pressed(&keyboard_input) {
         match menu_action {
             LoseMenuButtonAction::Okay => {
-                app_state.set(GameState::MainMenu).unwrap();
+                menu_events.send(MenuEvent::Selected);
+                // play-tests go back to the level being edited
+                let next_state = if play_test.is_some() {
+                    GameState::Editor
+                } else {
+                    GameState::MainMenu
+                };
+                app_state.set(next_state).unwrap();
                 keyboard_input.clear();
             }
         }
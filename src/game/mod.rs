@@ -1,12 +1,57 @@
+mod autoplayer;
 mod ball;
+mod ball_trail;
 mod bricks;
+mod camera_effects;
+mod clock;
 mod components;
+mod difficulty;
+mod editor_state;
+mod endless;
+mod events;
+mod game_mode;
 mod game_state;
+mod hud;
+mod level_generator;
+mod level_layout;
+mod lose_state;
 mod paddle;
+mod particles;
 mod pause_state;
+mod rng;
+mod score_popups;
+mod scoring;
+mod speed_up;
+mod time_attack;
+mod versus;
 mod walls;
-mod lose_state;
 
 pub mod prelude {
+    // the ones behind features are only looked at by the debug overlay
+    pub use crate::game::autoplayer::{Autoplayer, BotSkill, Bots};
+    pub use crate::game::ball::{Ball, Velocity, BALL_SPEED};
+    pub use crate::game::ball_trail::BallTrailPlugin;
+    pub use crate::game::bricks::{BrickGrid, BrickKind};
+    pub use crate::game::camera_effects::CameraEffectsPlugin;
+    pub use crate::game::clock::GameClock;
+    pub use crate::game::components::Brick;
+    #[cfg(feature = "debug_overlay")]
+    pub use crate::game::components::Collider;
+    pub use crate::game::components::{GameConfig, GameData};
+    pub use crate::game::difficulty::{Difficulty, DifficultyParams};
+    pub use crate::game::editor_state::LevelFile;
+    pub use crate::game::events::*;
+    pub use crate::game::game_mode::GameMode;
     pub use crate::game::game_state::*;
+    pub use crate::game::level_generator::{LevelGenerator, Pattern};
+    pub use crate::game::level_layout::LevelLayout;
+    pub use crate::game::paddle::Paddle;
+    pub use crate::game::paddle::PADDLE_KEYS;
+    pub use crate::game::particles::ParticlesPlugin;
+    pub use crate::game::rng::GameRng;
+    pub use crate::game::score_popups::ScorePopupsPlugin;
+    #[cfg(feature = "debug_overlay")]
+    pub use crate::game::speed_up::RallySpeed;
+    pub use crate::game::speed_up::SpeedUpConfig;
+    pub use crate::game::walls::{X_OFFSET, Y_OFFSET};
 }
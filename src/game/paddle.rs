@@ -1,100 +1,115 @@
-use bevy::{math::const_vec3, prelude::*};
+use bevy::prelude::*;
 
-use crate::GameState;
+use crate::{
+    scaling::{LOGICAL_HEIGHT, LOGICAL_WIDTH},
+    theme::Theme,
+    GameState,
+};
 
 use super::{
-    components::{Collider, GameEntity},
+    clock::GameClock,
+    components::{Collider, GameConfig, GameEntity},
+    events::CollisionCheck,
+    game_mode::GameMode,
     walls::{X_OFFSET, Y_OFFSET},
 };
 
-const PADDLE_WIDTH: f32 = 120.0;
-const PADDLE_SIZE: Vec3 = const_vec3!([PADDLE_WIDTH, 20.0, 0.0]);
-const PADDLE_SPEED: f32 = 600.0;
+// the width and speed come from the difficulty
+const PADDLE_HEIGHT: f32 = 20.0;
 // How close can the paddle get to the wall
 const PADDLE_PADDING: f32 = 20.0;
-const PADDLE_COLOR: Color = Color::rgb(0.3, 0.3, 0.7);
 const GAP_BETWEEN_PADDLE_AND_FLOOR: f32 = 60.0;
+// How much higher the second player's paddle is in co-op, above where the ball is served
+const COOP_PADDLE_RAISE: f32 = 125.0;
+
+// The left and right keys of every player's paddle
+pub const PADDLE_KEYS: [(KeyCode, KeyCode); 2] =
+    [(KeyCode::Left, KeyCode::Right), (KeyCode::A, KeyCode::D)];
 
 #[derive(Component)]
 pub struct Paddle {
-    speed: f32,
+    pub speed: f32,
+    // the first player is 0, and the only one outside of two-player modes
+    pub player: usize,
 }
 
+// The ball passes through this paddle on its way up, so it doesn't shield the paddle below it
+#[derive(Component)]
+pub struct OneWay;
+
 pub struct PaddlePlugin;
 
 impl Plugin for PaddlePlugin {
     fn build(&self, app: &mut App) {
         app.add_system_set(SystemSet::on_enter(GameState::InGame).with_system(render_paddle))
             .add_system_set(
-                SystemSet::on_update(GameState::InGame).with_system(handle_paddle_move),
+                // moved before the ball is checked against it, otherwise which comes first is up
+                // to the threads and replays or training runs can play out differently
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(handle_paddle_move.before(CollisionCheck)),
             );
     }
 }
 
-fn render_paddle(mut commands: Commands, windows: Res<Windows>) {
-    let window = windows.get_primary().unwrap();
-    let window_height = window.height();
-    let paddle_position = -1.0 * (window_height / 2.0 + Y_OFFSET - GAP_BETWEEN_PADDLE_AND_FLOOR);
-
-    // paddle
-    commands
-        .spawn()
-        .insert(Paddle {
-            speed: PADDLE_SPEED,
-        })
-        .insert(Collider)
-        .insert(GameEntity)
-        .insert_bundle(SpriteBundle {
-            transform: Transform {
-                translation: Vec3::new(0.0, paddle_position, 1.0),
-                scale: PADDLE_SIZE,
-                ..default()
-            },
-            sprite: Sprite {
-                color: PADDLE_COLOR,
+fn render_paddle(mut commands: Commands, game_config: Res<GameConfig>, theme: Res<Theme>) {
+    let paddle_position = -(LOGICAL_HEIGHT / 2.0 + Y_OFFSET - GAP_BETWEEN_PADDLE_AND_FLOOR);
+    let params = game_config.difficulty_params();
+
+    for player in 0..game_config.mode.players() {
+        // in versus the second player's paddle is at the top, mirroring the first one, and in
+        // co-op it is above the first one
+        let y = match (game_config.mode, player) {
+            (GameMode::Versus, 1) => -paddle_position,
+            (GameMode::Coop, 1) => paddle_position + COOP_PADDLE_RAISE,
+            _ => paddle_position,
+        };
+
+        let mut paddle = commands.spawn();
+        if game_config.mode == GameMode::Coop && player == 1 {
+            paddle.insert(OneWay);
+        }
+        paddle
+            .insert(Paddle {
+                speed: params.paddle_speed,
+                player,
+            })
+            .insert(Collider)
+            .insert(GameEntity)
+            .insert_bundle(theme.paddle.sprite_bundle(Transform {
+                translation: Vec3::new(0.0, y, 1.0),
+                scale: Vec3::new(params.paddle_width, PADDLE_HEIGHT, 0.0),
                 ..default()
-            },
-            ..default()
-        });
+            }));
+    }
 }
 
 fn handle_paddle_move(
-    time: Res<Time>,
+    clock: Res<GameClock>,
     keyboard_input: Res<Input<KeyCode>>,
-    windows: Res<Windows>,
-    mut query: Query<(&mut Transform, &mut Paddle), With<Paddle>>,
+    mut query: Query<(&mut Transform, &Paddle)>,
 ) {
-    let window = windows.get_primary().unwrap();
-    let window_width = window.width();
-    let mut direction = 0.0;
-    let (mut player_transform, mut paddle) = query.single_mut();
+    for (mut player_transform, paddle) in query.iter_mut() {
+        let (left_key, right_key) = PADDLE_KEYS[paddle.player];
+        let mut direction = 0.0;
 
-    if keyboard_input.pressed(KeyCode::Right) {
-        direction += 1.0;
-    }
+        if keyboard_input.pressed(right_key) {
+            direction += 1.0;
+        }
 
-    if keyboard_input.pressed(KeyCode::Left) {
-        direction -= 1.0;
-    }
+        if keyboard_input.pressed(left_key) {
+            direction -= 1.0;
+        }
 
-    if keyboard_input.just_pressed(KeyCode::RBracket) {
-        paddle.speed += 50.0;
-        println!("increasing paddle speed {}", paddle.speed);
-    }
+        let new_position =
+            player_transform.translation.x + direction * paddle.speed * clock.delta_seconds();
+        let paddle_width = player_transform.scale.x;
+        let left_bound = -calculate_wall_boundary(paddle_width) + PADDLE_PADDING;
+        let right_bound = calculate_wall_boundary(paddle_width) - PADDLE_PADDING;
 
-    if keyboard_input.just_pressed(KeyCode::LBracket) {
-        paddle.speed -= 50.0;
-        println!("decreasing paddle speed {}", paddle.speed);
+        player_transform.translation.x = new_position.clamp(left_bound, right_bound);
     }
-
-    let new_position =
-        player_transform.translation.x + direction * paddle.speed * time.delta_seconds();
-    let left_bound = calculate_wall_boundary(window_width) * -1.0 + PADDLE_PADDING;
-    let right_bound = calculate_wall_boundary(window_width) - PADDLE_PADDING;
-
-    player_transform.translation.x = new_position.clamp(left_bound, right_bound);
 }
 
-fn calculate_wall_boundary(window_width: f32) -> f32 {
-    window_width / 2.0 + X_OFFSET - (PADDLE_WIDTH / 2.0)
+fn calculate_wall_boundary(paddle_width: f32) -> f32 {
+    LOGICAL_WIDTH / 2.0 + X_OFFSET - (paddle_width / 2.0)
 }
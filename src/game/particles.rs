@@ -0,0 +1,292 @@
+use std::ops::Range;
+
+use bevy::prelude::*;
+use rand::{rngs::ThreadRng, Rng};
+
+use crate::{settings::Settings, theme::Theme, utilities::despawn_entities, GameState};
+
+use super::{
+    clock::GameClock,
+    events::{BallHitPaddle, BallHitWall, BallLost, BrickDestroyed, BrickHit, CollisionCheck},
+};
+
+// Upper bound on the number of particles alive at once, emitters skip particles past it
+const MAX_PARTICLES: usize = 400;
+// Particles render above everything else in the arena
+const PARTICLE_Z: f32 = 5.0;
+const GRAVITY: f32 = 600.0;
+const SPARK_COLOR: Color = Color::rgb(1.0, 0.9, 0.6);
+
+#[derive(Component)]
+struct Particle {
+    velocity: Vec2,
+    gravity: f32,
+    age: f32,
+    lifetime: f32,
+    size: f32,
+    color: Color,
+}
+
+// Lets the particles of the last lost ball play out behind the game over menu, once the game's
+// own camera is gone
+#[derive(Component)]
+struct ParticleCamera;
+
+// Particles are hidden instead of despawned when they die, and reused by the next emission
+#[derive(Default)]
+struct ParticlePool {
+    free: Vec<Entity>,
+    alive: usize,
+}
+
+// What a single burst of particles looks like
+struct Emission {
+    position: Vec2,
+    color: Color,
+    count: usize,
+    // angles are in radians, measured counterclockwise from the right
+    angle: Range<f32>,
+    speed: Range<f32>,
+    size: Range<f32>,
+    lifetime: Range<f32>,
+    gravity: f32,
+}
+
+// Small CPU particle effects for brick breaks, ball impacts and losing the ball
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParticlePool>()
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(emit_particles.after(CollisionCheck))
+                    .with_system(update_particles),
+            )
+            .add_system_set(
+                SystemSet::on_enter(GameState::GameOver).with_system(spawn_particle_camera),
+            )
+            .add_system_set(SystemSet::on_update(GameState::GameOver).with_system(update_particles))
+            .add_system_set(
+                SystemSet::on_exit(GameState::GameOver)
+                    .with_system(despawn_entities::<ParticleCamera>),
+            );
+
+        // particles outlive the game they come from, until whatever comes after the game over menu
+        for state in [GameState::InGame, GameState::MainMenu, GameState::Editor] {
+            app.add_system_set(
+                SystemSet::on_enter(state)
+                    .with_system(despawn_entities::<Particle>)
+                    .with_system(clear_pool),
+            );
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn emit_particles(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    theme: Res<Theme>,
+    mut pool: ResMut<ParticlePool>,
+    mut paddle_hits: EventReader<BallHitPaddle>,
+    mut wall_hits: EventReader<BallHitWall>,
+    mut brick_hits: EventReader<BrickHit>,
+    mut bricks_destroyed: EventReader<BrickDestroyed>,
+    mut balls_lost: EventReader<BallLost>,
+) {
+    // the events still have to be read so they don't pile up once particles are turned back on
+    if !settings.particles {
+        paddle_hits.iter().for_each(drop);
+        wall_hits.iter().for_each(drop);
+        brick_hits.iter().for_each(drop);
+        bricks_destroyed.iter().for_each(drop);
+        balls_lost.iter().for_each(drop);
+        return;
+    }
+
+    // cosmetic only, so this doesn't take from the seeded game rng
+    let mut rng = rand::thread_rng();
+
+    for hit in paddle_hits.iter() {
+        emit(
+            &mut commands,
+            &mut pool,
+            &mut rng,
+            Emission {
+                position: hit.position,
+                color: SPARK_COLOR,
+                count: 10,
+                angle: 0.2..2.9,
+                speed: 120.0..280.0,
+                size: 3.0..5.0,
+                lifetime: 0.15..0.35,
+                gravity: 0.0,
+            },
+        );
+    }
+
+    for hit in wall_hits.iter() {
+        emit(
+            &mut commands,
+            &mut pool,
+            &mut rng,
+            Emission {
+                position: hit.position,
+                color: SPARK_COLOR,
+                count: 6,
+                angle: 0.0..std::f32::consts::TAU,
+                speed: 80.0..200.0,
+                size: 2.0..4.0,
+                lifetime: 0.1..0.25,
+                gravity: 0.0,
+            },
+        );
+    }
+
+    // a few chips off bricks that survive the hit, in the colour they had before it
+    for hit in brick_hits.iter() {
+        emit(
+            &mut commands,
+            &mut pool,
+            &mut rng,
+            Emission {
+                position: hit.position,
+                color: theme.brick(hit.kind, 2).color,
+                count: 6,
+                angle: 0.0..std::f32::consts::TAU,
+                speed: 60.0..180.0,
+                size: 2.0..4.0,
+                lifetime: 0.15..0.3,
+                gravity: GRAVITY,
+            },
+        );
+    }
+
+    for brick in bricks_destroyed.iter() {
+        emit(
+            &mut commands,
+            &mut pool,
+            &mut rng,
+            Emission {
+                position: brick.position,
+                color: theme.brick(brick.kind, 0).color,
+                count: 24,
+                angle: 0.0..std::f32::consts::TAU,
+                speed: 60.0..260.0,
+                size: 4.0..9.0,
+                lifetime: 0.4..0.8,
+                gravity: GRAVITY,
+            },
+        );
+    }
+
+    for lost in balls_lost.iter() {
+        emit(
+            &mut commands,
+            &mut pool,
+            &mut rng,
+            Emission {
+                position: lost.position,
+                color: theme.ball.color,
+                count: 60,
+                angle: 0.3..2.8,
+                speed: 150.0..450.0,
+                size: 4.0..8.0,
+                lifetime: 0.6..1.2,
+                gravity: GRAVITY,
+            },
+        );
+    }
+}
+
+fn emit(commands: &mut Commands, pool: &mut ParticlePool, rng: &mut ThreadRng, emission: Emission) {
+    let count = emission.count.min(MAX_PARTICLES.saturating_sub(pool.alive));
+
+    for _ in 0..count {
+        let angle = rng.gen_range(emission.angle.clone());
+        let particle = Particle {
+            velocity: Vec2::new(angle.cos(), angle.sin()) * rng.gen_range(emission.speed.clone()),
+            gravity: emission.gravity,
+            age: 0.0,
+            lifetime: rng.gen_range(emission.lifetime.clone()),
+            size: rng.gen_range(emission.size.clone()),
+            color: emission.color,
+        };
+        let sprite = Sprite {
+            color: particle.color,
+            custom_size: Some(Vec2::splat(particle.size)),
+            ..default()
+        };
+        let transform = Transform::from_translation(emission.position.extend(PARTICLE_Z));
+
+        match pool.free.pop() {
+            Some(entity) => {
+                commands
+                    .entity(entity)
+                    .insert(particle)
+                    .insert(sprite)
+                    .insert(transform)
+                    .insert(Visibility { is_visible: true });
+            }
+            None => {
+                commands
+                    .spawn_bundle(SpriteBundle {
+                        sprite,
+                        transform,
+                        ..default()
+                    })
+                    .insert(particle);
+            }
+        }
+        pool.alive += 1;
+    }
+}
+
+fn update_particles(
+    clock: Res<GameClock>,
+    mut pool: ResMut<ParticlePool>,
+    mut query: Query<(
+        Entity,
+        &mut Particle,
+        &mut Transform,
+        &mut Sprite,
+        &mut Visibility,
+    )>,
+) {
+    let delta = clock.delta_seconds();
+
+    for (entity, mut particle, mut transform, mut sprite, mut visibility) in query.iter_mut() {
+        if !visibility.is_visible {
+            continue;
+        }
+
+        particle.age += delta;
+        if particle.age >= particle.lifetime {
+            visibility.is_visible = false;
+            pool.free.push(entity);
+            pool.alive -= 1;
+            continue;
+        }
+
+        particle.velocity.y -= particle.gravity * delta;
+        transform.translation.x += particle.velocity.x * delta;
+        transform.translation.y += particle.velocity.y * delta;
+
+        // particles shrink and fade out over their lifetime
+        let remaining = 1.0 - particle.age / particle.lifetime;
+        sprite.custom_size = Some(Vec2::splat(particle.size * remaining));
+        sprite.color = particle.color;
+        sprite.color.set_a(particle.color.a() * remaining);
+    }
+}
+
+fn spawn_particle_camera(mut commands: Commands) {
+    commands
+        .spawn_bundle(OrthographicCameraBundle::new_2d())
+        .insert(ParticleCamera);
+}
+
+fn clear_pool(mut pool: ResMut<ParticlePool>) {
+    *pool = ParticlePool::default();
+}
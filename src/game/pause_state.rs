@@ -3,11 +3,14 @@ use bevy::prelude::*;
 use crate::{
     state_plugin::StateChange,
     utilities::{
-        self, despawn_entities, MenuButtonAction, MenuEntity, MenuOptions, SelectedOption,
+        self, despawn_entities, MenuButtonAction, MenuEntity, MenuEvent, MenuOptions, MenuStyle,
+        SelectedOption,
     },
     GameState,
 };
 
+use super::editor_state::PlayTest;
+
 pub struct PausePlugin;
 
 impl Plugin for PausePlugin {
@@ -24,14 +27,25 @@ impl Plugin for PausePlugin {
     }
 }
 
-fn render_menu(commands: Commands, asset_server: Res<AssetServer>, windows: Res<Windows>) {
+fn render_menu(
+    commands: Commands,
+    asset_server: Res<AssetServer>,
+    play_test: Option<Res<PlayTest>>,
+) {
+    let mut buttons = vec![("Continue".to_string(), MenuButtonAction::Play)];
+    // play-tests can be cut short without losing the level being edited
+    if play_test.is_some() {
+        buttons.push(("Back to editor".to_string(), MenuButtonAction::Editor));
+    }
+    buttons.push(("Quit".to_string(), MenuButtonAction::Quit));
+
     utilities::render_menu(
         commands,
         asset_server,
-        windows,
         MenuOptions {
             title: "Paused",
-            play_text: "Continue",
+            buttons,
+            style: MenuStyle::LARGE,
         },
     );
 }
@@ -40,17 +54,23 @@ fn select_menu_item(
     keyboard_input: ResMut<Input<KeyCode>>,
     selected_option_query: Query<&MenuButtonAction, With<SelectedOption>>,
     mut event_state_change: EventWriter<StateChange>,
+    mut menu_events: EventWriter<MenuEvent>,
 ) {
     let menu_action = selected_option_query.single();
 
-    if keyboard_input.just_pressed(KeyCode::Return) {
+    if utilities::confirm_pressed(&keyboard_input) {
+        menu_events.send(MenuEvent::Selected);
         match menu_action {
             MenuButtonAction::Play => {
                 event_state_change.send(StateChange::Pop);
             }
+            MenuButtonAction::Editor => {
+                event_state_change.send(StateChange::Replace(GameState::Editor));
+            }
             MenuButtonAction::Quit => {
                 event_state_change.send(StateChange::Exit);
             }
+            _ => {}
         }
     }
 }
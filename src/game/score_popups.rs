@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+
+use crate::GameState;
+
+use super::{
+    clock::GameClock,
+    components::GameEntity,
+    events::{PointsScored, ScoreReason},
+    scoring::ScoreUpdate,
+};
+
+const POPUP_FONT_SIZE: f32 = 24.0;
+const BONUS_FONT_SIZE: f32 = 36.0;
+const POPUP_COLOR: Color = Color::rgb(1.0, 0.95, 0.6);
+const POPUP_LIFETIME: f32 = 0.8;
+const BONUS_LIFETIME: f32 = 2.0;
+// How fast popups float up, in logical units per second
+const POPUP_RISE_SPEED: f32 = 60.0;
+// Above the bricks, the ball and the particles
+const POPUP_Z: f32 = 10.0;
+
+#[derive(Component)]
+struct ScorePopup {
+    age: f32,
+    lifetime: f32,
+}
+
+// Floating "+N" texts where points were scored
+pub struct ScorePopupsPlugin;
+
+impl Plugin for ScorePopupsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_update(GameState::InGame)
+                .with_system(spawn_popups.after(ScoreUpdate))
+                .with_system(update_popups),
+        );
+    }
+}
+
+fn spawn_popups(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut points_scored: EventReader<PointsScored>,
+) {
+    for scored in points_scored.iter() {
+        let (text, font_size, lifetime) = match scored.reason {
+            ScoreReason::Brick => (
+                format!("+{}", scored.points),
+                POPUP_FONT_SIZE,
+                POPUP_LIFETIME,
+            ),
+            ScoreReason::QuickClear => (
+                format!("Quick clear +{}", scored.points),
+                BONUS_FONT_SIZE,
+                BONUS_LIFETIME,
+            ),
+            ScoreReason::Flawless => (
+                format!("No lives lost +{}", scored.points),
+                BONUS_FONT_SIZE,
+                BONUS_LIFETIME,
+            ),
+        };
+
+        commands
+            .spawn_bundle(Text2dBundle {
+                text: Text::with_section(
+                    text,
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size,
+                        color: POPUP_COLOR,
+                    },
+                    TextAlignment {
+                        vertical: VerticalAlign::Center,
+                        horizontal: HorizontalAlign::Center,
+                    },
+                ),
+                transform: Transform::from_translation(scored.position.extend(POPUP_Z)),
+                ..default()
+            })
+            .insert(ScorePopup { age: 0.0, lifetime })
+            .insert(GameEntity);
+    }
+}
+
+fn update_popups(
+    mut commands: Commands,
+    clock: Res<GameClock>,
+    mut popup_query: Query<(Entity, &mut ScorePopup, &mut Transform, &mut Text)>,
+) {
+    let delta = clock.delta_seconds();
+
+    for (entity, mut popup, mut transform, mut text) in popup_query.iter_mut() {
+        popup.age += delta;
+        if popup.age >= popup.lifetime {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        transform.translation.y += POPUP_RISE_SPEED * delta;
+        // fade out over the second half of the popup's life
+        let alpha = (2.0 * (1.0 - popup.age / popup.lifetime)).min(1.0);
+        for section in text.sections.iter_mut() {
+            section.style.color.set_a(alpha);
+        }
+    }
+}
@@ -0,0 +1,195 @@
+use bevy::prelude::*;
+
+use crate::GameState;
+
+use super::{
+    clock::GameClock,
+    components::{GameConfig, GameData},
+    events::{
+        BallHitPaddle, BallLost, BrickDestroyed, CollisionCheck, LevelCleared, PointsScored,
+        ScoreReason,
+    },
+};
+
+// Every this many bricks in a combo add one to the multiplier
+const BRICKS_PER_MULTIPLIER: u32 = 4;
+const MAX_COMBO_MULTIPLIER: u32 = 4;
+// Clearing a level faster than this earns points for every second left
+const QUICK_CLEAR_SECONDS: f32 = 90.0;
+const QUICK_CLEAR_POINTS_PER_SECOND: f32 = 5.0;
+// For clearing a level without losing a life
+const FLAWLESS_BONUS: i32 = 250;
+
+// Label of the system keeping the score and combo
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ScoreUpdate;
+
+// Bricks broken since the ball last touched the paddle
+#[derive(Debug, Default)]
+pub struct Combo {
+    pub count: u32,
+}
+
+impl Combo {
+    pub fn multiplier(&self) -> u32 {
+        (1 + self.count / BRICKS_PER_MULTIPLIER).min(MAX_COMBO_MULTIPLIER)
+    }
+}
+
+// How the current level is going, for the bonuses at the end of it
+#[derive(Default)]
+struct LevelProgress {
+    elapsed: f32,
+    lives_lost: u32,
+}
+
+impl LevelProgress {
+    // The bonuses for clearing the level now, before the difficulty multiplier
+    fn bonuses(&self) -> Vec<(ScoreReason, f32)> {
+        let mut bonuses = Vec::new();
+        let seconds_left = (QUICK_CLEAR_SECONDS - self.elapsed).max(0.0);
+        if seconds_left > 0.0 {
+            bonuses.push((
+                ScoreReason::QuickClear,
+                seconds_left.floor() * QUICK_CLEAR_POINTS_PER_SECOND,
+            ));
+        }
+        if self.lives_lost == 0 {
+            bonuses.push((ScoreReason::Flawless, FLAWLESS_BONUS as f32));
+        }
+        bonuses
+    }
+}
+
+// Points for bricks, multiplied by the combo and the difficulty, and bonuses for clearing levels
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Combo>()
+            .init_resource::<LevelProgress>()
+            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(reset_scoring))
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(update_score.label(ScoreUpdate).after(CollisionCheck)),
+            );
+    }
+}
+
+fn reset_scoring(mut combo: ResMut<Combo>, mut progress: ResMut<LevelProgress>) {
+    *combo = Combo::default();
+    *progress = LevelProgress::default();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_score(
+    clock: Res<GameClock>,
+    game_config: Res<GameConfig>,
+    mut game_data: ResMut<GameData>,
+    mut combo: ResMut<Combo>,
+    mut progress: ResMut<LevelProgress>,
+    mut paddle_hits: EventReader<BallHitPaddle>,
+    mut bricks_destroyed: EventReader<BrickDestroyed>,
+    mut balls_lost: EventReader<BallLost>,
+    mut levels_cleared: EventReader<LevelCleared>,
+    mut points_scored: EventWriter<PointsScored>,
+) {
+    let difficulty_multiplier = game_config.difficulty_params().score_multiplier;
+    let mut score = |points: f32, position: Vec2, reason: ScoreReason| {
+        let points = (points * difficulty_multiplier).round() as i32;
+        game_data.score = game_data.score.saturating_add(points);
+        points_scored.send(PointsScored {
+            position,
+            points,
+            reason,
+        });
+    };
+
+    progress.elapsed += clock.delta_seconds();
+
+    // touching the paddle ends the combo, and any brick broken in the same frame starts a new one
+    if paddle_hits.iter().count() > 0 {
+        combo.count = 0;
+    }
+
+    for brick in bricks_destroyed.iter() {
+        combo.count += 1;
+        score(
+            (brick.points as u32 * combo.multiplier()) as f32,
+            brick.position,
+            ScoreReason::Brick,
+        );
+    }
+
+    let lives_lost = balls_lost.iter().count() as u32;
+    if lives_lost > 0 {
+        combo.count = 0;
+        progress.lives_lost += lives_lost;
+    }
+
+    if levels_cleared.iter().count() > 0 {
+        for (reason, points) in progress.bonuses() {
+            // the bonuses pop up one above the other in the middle of the arena
+            let y = if reason == ScoreReason::QuickClear {
+                20.0
+            } else {
+                -20.0
+            };
+            score(points, Vec2::new(0.0, y), reason);
+        }
+
+        // the ball is served again for the next level
+        combo.count = 0;
+        *progress = LevelProgress::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn combos_raise_the_multiplier_up_to_a_limit() {
+        for (count, multiplier) in [(0, 1), (3, 1), (4, 2), (7, 2), (8, 3), (12, 4), (100, 4)] {
+            assert_eq!(Combo { count }.multiplier(), multiplier, "{} bricks", count);
+        }
+    }
+
+    #[test]
+    fn quick_clears_earn_points_for_every_second_left() {
+        let progress = LevelProgress {
+            elapsed: 30.5,
+            lives_lost: 1,
+        };
+        assert_eq!(
+            progress.bonuses(),
+            vec![(ScoreReason::QuickClear, 59.0 * 5.0)]
+        );
+
+        for elapsed in [QUICK_CLEAR_SECONDS, 120.0] {
+            let progress = LevelProgress {
+                elapsed,
+                lives_lost: 1,
+            };
+            assert!(progress.bonuses().is_empty(), "{} seconds", elapsed);
+        }
+    }
+
+    #[test]
+    fn flawless_clears_earn_a_bonus() {
+        let progress = LevelProgress {
+            elapsed: 200.0,
+            lives_lost: 0,
+        };
+        assert_eq!(
+            progress.bonuses(),
+            vec![(ScoreReason::Flawless, FLAWLESS_BONUS as f32)]
+        );
+
+        let progress = LevelProgress {
+            elapsed: 10.0,
+            lives_lost: 0,
+        };
+        assert_eq!(progress.bonuses().len(), 2);
+    }
+}
@@ -0,0 +1,149 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::GameState;
+
+use super::{
+    ball::{Ball, Velocity},
+    clock::GameClock,
+    components::GameConfig,
+    events::{BallHitPaddle, BallLost, BallReset, BrickDestroyed, LevelCleared},
+};
+
+// When and by how much the ball speeds up during a rally, part of the game's `GameConfig`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpeedUpConfig {
+    // the ball speeds up every this many paddle hits, 0 turns this off
+    pub paddle_hits_per_level: u32,
+    // the ball speeds up every this many seconds, 0 turns this off
+    pub seconds_per_level: f32,
+    // the ball speeds up the first time it breaks a brick in the top row
+    pub top_row: bool,
+    // extra speed per level, as a fraction of the speed the ball was served at
+    pub step: f32,
+    pub max_level: u32,
+}
+
+impl Default for SpeedUpConfig {
+    fn default() -> Self {
+        SpeedUpConfig {
+            paddle_hits_per_level: 6,
+            seconds_per_level: 20.0,
+            top_row: true,
+            step: 0.1,
+            max_level: 5,
+        }
+    }
+}
+
+// How far the ball has sped up since it was last served
+#[derive(Debug)]
+pub struct RallySpeed {
+    pub level: u32,
+    paddle_hits: u32,
+    elapsed: f32,
+    reached_top_row: bool,
+    // the multiplier the ball's velocity currently has
+    multiplier: f32,
+}
+
+impl Default for RallySpeed {
+    fn default() -> Self {
+        RallySpeed {
+            level: 0,
+            paddle_hits: 0,
+            elapsed: 0.0,
+            reached_top_row: false,
+            multiplier: 1.0,
+        }
+    }
+}
+
+impl RallySpeed {
+    #[cfg_attr(not(feature = "debug_overlay"), allow(dead_code))]
+    pub fn multiplier(&self) -> f32 {
+        self.multiplier
+    }
+}
+
+// Speeds the ball up as a rally goes on. Losing a life or clearing a level serves the ball again
+// at its normal speed.
+pub struct SpeedUpPlugin;
+
+impl Plugin for SpeedUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RallySpeed>()
+            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(reset_rally_speed))
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(update_rally_speed.after(BallReset)),
+            );
+    }
+}
+
+fn reset_rally_speed(mut rally_speed: ResMut<RallySpeed>) {
+    *rally_speed = RallySpeed::default();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_rally_speed(
+    game_config: Res<GameConfig>,
+    clock: Res<GameClock>,
+    mut rally_speed: ResMut<RallySpeed>,
+    mut paddle_hits: EventReader<BallHitPaddle>,
+    mut bricks_destroyed: EventReader<BrickDestroyed>,
+    mut balls_lost: EventReader<BallLost>,
+    mut levels_cleared: EventReader<LevelCleared>,
+    mut ball_query: Query<&mut Velocity, With<Ball>>,
+) {
+    let config = &game_config.speed_up;
+
+    // the ball was just served again at its normal speed
+    if balls_lost.iter().count() > 0 || levels_cleared.iter().count() > 0 {
+        paddle_hits.iter().for_each(drop);
+        bricks_destroyed.iter().for_each(drop);
+        *rally_speed = RallySpeed::default();
+        return;
+    }
+
+    let mut steps = 0;
+
+    rally_speed.paddle_hits += paddle_hits.iter().count() as u32;
+    if let Some(paddle_steps) = rally_speed
+        .paddle_hits
+        .checked_div(config.paddle_hits_per_level)
+    {
+        steps += paddle_steps;
+        rally_speed.paddle_hits %= config.paddle_hits_per_level;
+    }
+
+    if config.seconds_per_level > 0.0 {
+        rally_speed.elapsed += clock.delta_seconds();
+        if rally_speed.elapsed >= config.seconds_per_level {
+            rally_speed.elapsed -= config.seconds_per_level;
+            steps += 1;
+        }
+    }
+
+    let top_row_broken = bricks_destroyed
+        .iter()
+        .filter(|brick| brick.row == 0)
+        .count()
+        > 0;
+    if config.top_row && top_row_broken && !rally_speed.reached_top_row {
+        rally_speed.reached_top_row = true;
+        steps += 1;
+    }
+
+    if steps == 0 || rally_speed.level >= config.max_level {
+        return;
+    }
+
+    rally_speed.level = (rally_speed.level + steps).min(config.max_level);
+    let multiplier = 1.0 + config.step * rally_speed.level as f32;
+    for mut velocity in ball_query.iter_mut() {
+        **velocity *= multiplier / rally_speed.multiplier;
+    }
+    rally_speed.multiplier = multiplier;
+}
@@ -0,0 +1,113 @@
+use bevy::prelude::*;
+use rand::seq::index;
+
+use crate::GameState;
+
+use super::{
+    bricks::BrickKind,
+    clock::GameClock,
+    components::{Brick, GameConfig},
+    events::{BrickDestroyed, CollisionCheck, LevelCleared},
+    game_mode::GameMode,
+    game_state::lose_life,
+    rng::GameRng,
+};
+
+// Time on the clock when the game starts
+const STARTING_TIME: f32 = 120.0;
+// Added to the clock by every time brick
+const TIME_BRICK_SECONDS: f32 = 10.0;
+// Fraction of the normal bricks of every level that become time bricks, at least one
+const TIME_BRICK_FRACTION: f32 = 0.1;
+
+// The clock and what was done before it ran out, for the HUD and the results screen
+#[derive(Default)]
+pub struct TimeAttack {
+    pub time_left: f32,
+    // added by time bricks
+    pub bonus_time: f32,
+    pub bricks_broken: u32,
+    pub levels_cleared: u32,
+}
+
+impl TimeAttack {
+    pub fn is_over(&self) -> bool {
+        self.time_left <= 0.0
+    }
+}
+
+// Time attack: levels as usual, against a clock that only time bricks can wind back. The game is
+// over when the time is up, or as usual when the ball is lost too often.
+pub struct TimeAttackPlugin;
+
+impl Plugin for TimeAttackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TimeAttack>()
+            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(start_clock))
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    // before the other systems using the generator, to keep replays in step
+                    .with_system(add_time_bricks.before(CollisionCheck))
+                    .with_system(update_clock.after(CollisionCheck).after(lose_life)),
+            );
+    }
+}
+
+fn start_clock(mut time_attack: ResMut<TimeAttack>) {
+    *time_attack = TimeAttack {
+        time_left: STARTING_TIME,
+        ..default()
+    };
+}
+
+// Turns some of the normal bricks of every new level into time bricks
+fn add_time_bricks(
+    game_config: Res<GameConfig>,
+    mut rng: ResMut<GameRng>,
+    mut brick_query: Query<&mut Brick, Added<Brick>>,
+) {
+    if game_config.mode != GameMode::TimeAttack {
+        return;
+    }
+
+    let mut bricks: Vec<_> = brick_query
+        .iter_mut()
+        .filter(|brick| brick.kind == BrickKind::Normal)
+        .collect();
+    if bricks.is_empty() {
+        return;
+    }
+
+    let count = ((bricks.len() as f32 * TIME_BRICK_FRACTION).ceil() as usize).max(1);
+    for i in index::sample(&mut **rng, bricks.len(), count) {
+        bricks[i].kind = BrickKind::Time;
+    }
+}
+
+pub(super) fn update_clock(
+    clock: Res<GameClock>,
+    game_config: Res<GameConfig>,
+    mut time_attack: ResMut<TimeAttack>,
+    mut app_state: ResMut<State<GameState>>,
+    mut bricks_destroyed: EventReader<BrickDestroyed>,
+    mut levels_cleared: EventReader<LevelCleared>,
+) {
+    if game_config.mode != GameMode::TimeAttack {
+        return;
+    }
+
+    for brick_destroyed in bricks_destroyed.iter() {
+        time_attack.bricks_broken += 1;
+        if brick_destroyed.kind == BrickKind::Time {
+            time_attack.time_left += TIME_BRICK_SECONDS;
+            time_attack.bonus_time += TIME_BRICK_SECONDS;
+        }
+    }
+    time_attack.levels_cleared += levels_cleared.iter().count() as u32;
+
+    time_attack.time_left = (time_attack.time_left - clock.delta_seconds()).max(0.0);
+    if time_attack.is_over() {
+        // losing the last life in the same frame has already ended the game
+        let _ = app_state.set(GameState::GameOver);
+    }
+}
@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+
+use crate::{theme::Theme, GameState};
+
+use super::{
+    bricks::{self, BrickGrid},
+    components::GameConfig,
+    events::{BallHitPaddle, BallLost, CollisionCheck, LevelCleared, PointsScored},
+    game_mode::GameMode,
+    level_layout::LevelLayout,
+    scoring::ScoreUpdate,
+};
+
+// Rows of bricks across the middle of the arena, between the two paddles
+const BAND_ROWS: usize = 3;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VersusPlayer {
+    pub score: i32,
+    pub lives: u32,
+}
+
+// Score and lives of both players. The points for bricks go to whoever touched the ball last.
+#[derive(Default)]
+pub struct Versus {
+    pub players: [VersusPlayer; 2],
+    last_hit_by: usize,
+}
+
+impl Versus {
+    // The player with lives left once the game is over
+    pub fn winner(&self) -> Option<usize> {
+        self.players.iter().position(|player| player.lives > 0)
+    }
+}
+
+// Versus: the first player at the bottom and the second at the top, breaking a shared band of
+// bricks in the middle. Losing the ball on your own side costs a life, and the game is over when
+// either player has none left.
+pub struct VersusPlugin;
+
+impl Plugin for VersusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Versus>()
+            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(start_versus))
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame)
+                    .with_system(refill_band.after(CollisionCheck))
+                    .with_system(update_players.after(ScoreUpdate)),
+            );
+    }
+}
+
+fn start_versus(
+    mut commands: Commands,
+    game_config: Res<GameConfig>,
+    theme: Res<Theme>,
+    mut versus: ResMut<Versus>,
+) {
+    if game_config.mode != GameMode::Versus {
+        return;
+    }
+
+    let lives = game_config.difficulty_params().lives;
+    *versus = Versus {
+        players: [VersusPlayer { score: 0, lives }; 2],
+        ..default()
+    };
+    // `GameData` may not have been reset for the new game yet
+    spawn_band(&mut commands, &theme, game_config.starting_level);
+}
+
+// The middle row of the level's usual bricks, with its top row above and below it, so both
+// players face the same bricks
+fn spawn_band(commands: &mut Commands, theme: &Theme, level: u32) {
+    let grid = BrickGrid::default();
+    let layout = LevelLayout::generated(level, &grid);
+    let layout_rows: [usize; BAND_ROWS] = [0, grid.n_rows / 2, 0];
+
+    let row_height = grid.cell_position(0, 0).y - grid.cell_position(1, 0).y;
+    for (band_row, layout_row) in layout_rows.into_iter().enumerate() {
+        let y = ((BAND_ROWS - 1) as f32 / 2.0 - band_row as f32) * row_height;
+        for column in 0..grid.n_columns {
+            if let Some(kind) = layout.get(layout_row, column) {
+                let x = grid.cell_position(layout_row, column).x;
+                bricks::spawn_brick(commands, theme, kind, band_row as u32, Vec2::new(x, y));
+            }
+        }
+    }
+}
+
+fn refill_band(
+    mut commands: Commands,
+    game_config: Res<GameConfig>,
+    theme: Res<Theme>,
+    mut levels_cleared: EventReader<LevelCleared>,
+) {
+    if let Some(level_cleared) = levels_cleared.iter().last() {
+        if game_config.mode == GameMode::Versus {
+            spawn_band(&mut commands, &theme, level_cleared.level + 1);
+        }
+    }
+}
+
+fn update_players(
+    game_config: Res<GameConfig>,
+    mut versus: ResMut<Versus>,
+    mut app_state: ResMut<State<GameState>>,
+    mut paddle_hits: EventReader<BallHitPaddle>,
+    mut balls_lost: EventReader<BallLost>,
+    mut points_scored: EventReader<PointsScored>,
+) {
+    if game_config.mode != GameMode::Versus {
+        return;
+    }
+
+    if let Some(paddle_hit) = paddle_hits.iter().last() {
+        versus.last_hit_by = paddle_hit.player;
+    }
+    for points_scored in points_scored.iter() {
+        let player = versus.last_hit_by;
+        let score = &mut versus.players[player].score;
+        *score = score.saturating_add(points_scored.points);
+    }
+
+    for ball_lost in balls_lost.iter() {
+        let player = &mut versus.players[ball_lost.player];
+        player.lives = player.lives.saturating_sub(1);
+        if player.lives == 0 {
+            // a time limit or the other player leaving in the same frame may have ended the game
+            // already
+            let _ = app_state.set(GameState::GameOver);
+            return;
+        }
+    }
+}
@@ -1,11 +1,22 @@
+// `derive(Bundle)` forgets the fields it moves out of `WallBundle`, and the impl it generates
+// can only be reached with an allow on the whole module
+#![allow(clippy::forget_non_drop)]
+
 use bevy::prelude::*;
 
-use crate::{utilities::despawn_entities, GameState};
+use crate::{
+    scaling::{LOGICAL_HEIGHT, LOGICAL_WIDTH},
+    theme::Theme,
+    utilities::despawn_entities,
+    GameState,
+};
 
-use super::components::{Collider, FailZone};
+use super::{
+    components::{Collider, FailZone, GameConfig},
+    game_mode::GameMode,
+};
 
 const WALL_THICKNESS: f32 = 10.0;
-const WALL_COLOR: Color = Color::rgb(0.8, 0.8, 0.8);
 pub const X_OFFSET: f32 = -100.0;
 pub const Y_OFFSET: f32 = -15.0;
 
@@ -16,24 +27,28 @@ pub struct WallsPlugin;
 
 impl Plugin for WallsPlugin {
     fn build(&self, app: &mut App) {
-        app.add_system_set(SystemSet::on_enter(GameState::InGame).with_system(render_walls))
-            .add_system_set(
-                SystemSet::on_exit(GameState::InGame).with_system(despawn_entities::<Wall>),
-            );
+        // the level editor shows the arena too
+        for state in [GameState::InGame, GameState::Editor] {
+            app.add_system_set(SystemSet::on_enter(state).with_system(render_walls))
+                .add_system_set(SystemSet::on_exit(state).with_system(despawn_entities::<Wall>));
+        }
     }
 }
 
-fn render_walls(mut commands: Commands, windows: Res<Windows>) {
-    let window = windows.get_primary().unwrap();
-
-    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
-    commands.spawn_bundle(WallBundle::new(WallLocation::Left, window));
-    commands.spawn_bundle(WallBundle::new(WallLocation::Right, window));
-    commands.spawn_bundle(WallBundle::new(WallLocation::Top, window));
-    commands.spawn_bundle(WallBundle::new(WallLocation::Bottom, window)).insert(FailZone);
+fn render_walls(mut commands: Commands, game_config: Res<GameConfig>, theme: Res<Theme>) {
+    commands.spawn_bundle(WallBundle::new(WallLocation::Left, &theme));
+    commands.spawn_bundle(WallBundle::new(WallLocation::Right, &theme));
+    commands
+        .spawn_bundle(WallBundle::new(WallLocation::Bottom, &theme))
+        .insert(FailZone { player: 0 });
+
+    // the second player in versus defends the top
+    let mut top = commands.spawn_bundle(WallBundle::new(WallLocation::Top, &theme));
+    if game_config.mode == GameMode::Versus {
+        top.insert(FailZone { player: 1 });
+    }
 }
 
-
 enum WallLocation {
     Left,
     Right,
@@ -42,23 +57,18 @@ enum WallLocation {
 }
 
 impl WallLocation {
-    fn position(&self, window: &Window) -> Vec2 {
-        let window_height = window.height();
-        let window_width = window.width();
-
+    fn position(&self) -> Vec2 {
         match self {
-            WallLocation::Left => Vec2::new((window_width / 2.0 + X_OFFSET) * -1.0, 0.),
-            WallLocation::Right => Vec2::new(window_width / 2.0 + X_OFFSET, 0.),
-            WallLocation::Bottom => Vec2::new(0., (window_height / 2.0 + Y_OFFSET) * -1.0),
-            WallLocation::Top => Vec2::new(0., window_height / 2.0 + Y_OFFSET),
+            WallLocation::Left => Vec2::new(-(LOGICAL_WIDTH / 2.0 + X_OFFSET), 0.),
+            WallLocation::Right => Vec2::new(LOGICAL_WIDTH / 2.0 + X_OFFSET, 0.),
+            WallLocation::Bottom => Vec2::new(0., -(LOGICAL_HEIGHT / 2.0 + Y_OFFSET)),
+            WallLocation::Top => Vec2::new(0., LOGICAL_HEIGHT / 2.0 + Y_OFFSET),
         }
     }
 
-    fn size(&self, window: &Window) -> Vec2 {
-        let window_height = window.height();
-        let window_width = window.width();
-        let arena_side_size = (window_height / 2.0 + Y_OFFSET) * 2.0 + WALL_THICKNESS;
-        let arena_top_size = (window_width / 2.0 + X_OFFSET) * 2.0 + WALL_THICKNESS;
+    fn size(&self) -> Vec2 {
+        let arena_side_size = (LOGICAL_HEIGHT / 2.0 + Y_OFFSET) * 2.0 + WALL_THICKNESS;
+        let arena_top_size = (LOGICAL_WIDTH / 2.0 + X_OFFSET) * 2.0 + WALL_THICKNESS;
 
         match self {
             WallLocation::Left => Vec2::new(WALL_THICKNESS, arena_side_size),
@@ -80,20 +90,13 @@ struct WallBundle {
 impl WallBundle {
     // This "builder method" allows us to reuse logic across our wall entities,
     // making our code easier to read and less prone to bugs when we change the logic
-    fn new(location: WallLocation, window: &Window) -> WallBundle {
+    fn new(location: WallLocation, theme: &Theme) -> WallBundle {
         WallBundle {
-            sprite_bundle: SpriteBundle {
-                transform: Transform {
-                    translation: location.position(window).extend(0.0),
-                    scale: location.size(window).extend(1.0),
-                    ..default()
-                },
-                sprite: Sprite {
-                    color: WALL_COLOR,
-                    ..default()
-                },
+            sprite_bundle: theme.wall.sprite_bundle(Transform {
+                translation: location.position().extend(0.0),
+                scale: location.size().extend(1.0),
                 ..default()
-            },
+            }),
             wall: Wall,
             collider: Collider,
         }
@@ -0,0 +1,103 @@
+use std::time::Duration;
+
+use bevy::{
+    app::{AppExit, ScheduleRunnerSettings},
+    asset::AssetPlugin,
+    hierarchy::HierarchyPlugin,
+    input::InputPlugin,
+    prelude::*,
+    text::{Font, FontLoader},
+    transform::TransformPlugin,
+    window::WindowPlugin,
+};
+
+use crate::{
+    game::prelude::{GameClock, GameData, LevelCleared},
+    GameState,
+};
+
+// Nothing is drawn, so missing fonts and the absent UI systems the scaling is ordered against
+// aren't worth a warning
+pub const HEADLESS_LOG_FILTER: &str = "bevy_asset=error,bevy_ecs::schedule=error";
+
+// Every simulated frame advances the game by the same amount, independent of how fast it runs
+const HEADLESS_TIME_STEP: f32 = 1.0 / 60.0;
+
+// Game time after which a headless run ends, however the game is going, from the command line
+pub struct TimeLimit {
+    pub seconds: f32,
+    pub elapsed: f32,
+}
+
+// The engine pieces the game needs without a window or renderer, with every frame advancing the
+// game by the same amount. Shared by headless runs and the learning environment.
+pub struct SimulationPlugin;
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GameClock::fixed(HEADLESS_TIME_STEP))
+            .add_plugins(MinimalPlugins)
+            .add_plugin(TransformPlugin)
+            .add_plugin(HierarchyPlugin)
+            .add_plugin(InputPlugin)
+            .add_plugin(WindowPlugin::default())
+            .add_plugin(AssetPlugin)
+            // fonts still get loaded for the HUD, but nothing lays out or renders text
+            .add_asset::<Font>()
+            .init_asset_loader::<FontLoader>();
+    }
+}
+
+// Runs the game without a window or renderer, as fast as the machine allows. The game exits
+// and reports the final score as soon as it is over, which makes it usable from scripts.
+pub struct HeadlessPlugin;
+
+impl Plugin for HeadlessPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ScheduleRunnerSettings::run_loop(Duration::ZERO))
+            .add_plugin(SimulationPlugin)
+            .add_system_set(SystemSet::on_update(GameState::InGame).with_system(report_levels))
+            // after the frame, so it never clashes with the game ending on its own
+            .add_system_to_stage(CoreStage::PostUpdate, check_time_limit)
+            .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(report_and_exit));
+    }
+}
+
+// Long runs, like bots playing unattended, show how they're getting on
+fn report_levels(game_data: Res<GameData>, mut levels_cleared: EventReader<LevelCleared>) {
+    for level_cleared in levels_cleared.iter() {
+        println!(
+            "cleared level {} with a score of {}",
+            level_cleared.level, game_data.score
+        );
+    }
+}
+
+fn check_time_limit(
+    clock: Res<GameClock>,
+    time_limit: Option<ResMut<TimeLimit>>,
+    mut app_state: ResMut<State<GameState>>,
+) {
+    let mut time_limit = match time_limit {
+        Some(time_limit) => time_limit,
+        None => return,
+    };
+    if *app_state.current() != GameState::InGame {
+        return;
+    }
+
+    time_limit.elapsed += clock.delta_seconds();
+    if time_limit.elapsed >= time_limit.seconds {
+        println!("time limit of {} seconds reached", time_limit.seconds);
+        // the game may have ended on its own this frame
+        let _ = app_state.set(GameState::GameOver);
+    }
+}
+
+fn report_and_exit(game_data: Res<GameData>, mut exit: EventWriter<AppExit>) {
+    println!(
+        "game over: score {} on level {}",
+        game_data.score, game_data.level
+    );
+    exit.send(AppExit);
+}
@@ -0,0 +1,140 @@
+use std::{fs, path::Path};
+
+use bevy::{ecs::schedule::SystemLabel, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::prelude::{Bots, Difficulty, GameConfig, GameData, GameMode},
+    GameState,
+};
+
+const HIGH_SCORES_PATH: &str = "high_scores.ron";
+const MAX_SCORES_PER_DIFFICULTY: usize = 10;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighScore {
+    pub score: i32,
+    pub level: u32,
+    pub difficulty: Difficulty,
+    // scores from before there were modes are all classic ones
+    #[serde(default)]
+    pub mode: GameMode,
+}
+
+// The best scores for each mode and difficulty, so easy games don't crowd out hard ones. Saved to
+// `high_scores.ron` after every game. Custom difficulty games aren't kept, every settings file
+// plays a different game.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HighScores {
+    // sorted from best to worst
+    scores: Vec<HighScore>,
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        let path = Path::new(HIGH_SCORES_PATH);
+        if !path.exists() {
+            return HighScores::default();
+        }
+
+        match fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| ron::from_str(&contents).map_err(|err| err.to_string()))
+        {
+            Ok(high_scores) => high_scores,
+            Err(err) => {
+                warn!("could not read {}: {}", HIGH_SCORES_PATH, err);
+                HighScores::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                fs::write(HIGH_SCORES_PATH, contents).map_err(|err| err.to_string())
+            });
+
+        if let Err(err) = result {
+            warn!("could not save {}: {}", HIGH_SCORES_PATH, err);
+        }
+    }
+
+    pub fn best(&self, mode: GameMode, difficulty: Difficulty) -> Option<&HighScore> {
+        // files from before custom games were left out can still have some
+        if difficulty == Difficulty::Custom {
+            return None;
+        }
+
+        self.scores
+            .iter()
+            .find(|high_score| high_score.mode == mode && high_score.difficulty == difficulty)
+    }
+
+    pub fn add(&mut self, high_score: HighScore) {
+        let index = self
+            .scores
+            .iter()
+            .position(|other| other.score < high_score.score)
+            .unwrap_or(self.scores.len());
+        self.scores.insert(index, high_score);
+
+        // only keep the best few of every mode and difficulty
+        let mut kept = [[0; Difficulty::ALL.len()]; GameMode::ALL.len()];
+        self.scores.retain(|high_score| {
+            let count = &mut kept[high_score.mode as usize][high_score.difficulty as usize];
+            *count += 1;
+            *count <= MAX_SCORES_PER_DIFFICULTY
+        });
+    }
+}
+
+// What the high scores of a mode and difficulty are called, e.g. "Endless Hard"
+pub fn table_name(mode: GameMode, difficulty: Difficulty) -> String {
+    match mode {
+        GameMode::Classic => difficulty.name().to_string(),
+        _ => format!("{} {}", mode.name(), difficulty.name()),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub struct RecordHighScore;
+
+pub struct HighScoresPlugin;
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(HighScores::load()).add_system_set(
+            SystemSet::on_enter(GameState::GameOver)
+                .with_system(record_high_score.label(RecordHighScore)),
+        );
+    }
+}
+
+fn record_high_score(
+    game_config: Res<GameConfig>,
+    game_data: Res<GameData>,
+    bots: Res<Bots>,
+    mut high_scores: ResMut<HighScores>,
+) {
+    // levels from files and the editor aren't the same game, nor are custom difficulties, versus
+    // is about who wins, and a bot's score isn't the player's
+    if game_data.score <= 0
+        || game_config.layout.is_some()
+        || game_config.difficulty == Difficulty::Custom
+        || game_config.mode == GameMode::Versus
+        || bots.any_playing()
+    {
+        return;
+    }
+
+    high_scores.add(HighScore {
+        score: game_data.score,
+        level: game_data.level,
+        difficulty: game_config.difficulty,
+        mode: game_config.mode,
+    });
+    high_scores.save();
+}
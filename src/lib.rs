@@ -0,0 +1,210 @@
+mod cli;
+#[cfg(feature = "debug_overlay")]
+mod debug_overlay;
+mod display;
+pub mod env;
+mod game;
+mod headless;
+mod high_scores;
+mod menu_state;
+mod netcode;
+mod palette;
+mod replay;
+mod scaling;
+mod settings;
+mod settings_state;
+mod sound;
+#[cfg(feature = "spectator")]
+mod spectator;
+mod state_plugin;
+mod theme;
+mod utilities;
+
+#[cfg(feature = "debug_overlay")]
+use crate::debug_overlay::DebugOverlayPlugin;
+#[cfg(feature = "spectator")]
+use crate::spectator::{SpectatorPlugin, SpectatorPort};
+use crate::{
+    cli::LaunchOptions,
+    display::DisplayPlugin,
+    game::prelude::{
+        BallTrailPlugin, BrickGrid, CameraEffectsPlugin, GameConfig, GamePlugin, GameRng,
+        LevelFile, ParticlesPlugin, ScorePopupsPlugin,
+    },
+    headless::{HeadlessPlugin, TimeLimit, HEADLESS_LOG_FILTER},
+    high_scores::HighScoresPlugin,
+    menu_state::*,
+    netcode::NetcodePlugin,
+    replay::{Replay, ReplayPlayer, ReplayPlugin, ReplayRecorder},
+    scaling::ScalingPlugin,
+    settings::{Settings, SettingsPlugin},
+    settings_state::SettingsMenuPlugin,
+    sound::SoundPlugin,
+    state_plugin::*,
+    theme::ThemePlugin,
+    utilities::MenuEvent,
+};
+use bevy::{
+    log::{LogPlugin, LogSettings},
+    prelude::*,
+    window::WindowMode,
+};
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash, Copy)]
+pub enum GameState {
+    MainMenu,
+    InGame,
+    Paused,
+    GameOver,
+    Settings,
+    Editor,
+    Lobby,
+    Exit,
+}
+
+// Launches the game as the command line says, until it is closed
+pub fn run() {
+    let options = LaunchOptions::from_args();
+
+    // logging comes first, so reading the settings and exporting a level can already warn. It
+    // stays out of the headless simulation, as it can only be set up once per process.
+    let mut app = App::new();
+    if options.args.headless {
+        app.insert_resource(LogSettings {
+            filter: HEADLESS_LOG_FILTER.to_string(),
+            ..default()
+        });
+    }
+    app.add_plugin(LogPlugin);
+
+    let settings = Settings::load();
+    let rng = options.seed().map(GameRng::new).unwrap_or_default();
+    let game_config = GameConfig {
+        mode: options.mode(),
+        starting_level: options.starting_level(),
+        difficulty: options.difficulty(),
+        custom_difficulty: options
+            .custom_difficulty()
+            .unwrap_or(settings.custom_difficulty),
+        layout: options.layout(),
+        speed_up: options.speed_up().unwrap_or(settings.speed_up),
+    };
+
+    // exporting a generated level doesn't need the game itself
+    if let Some(path) = &options.args.export_level {
+        let layout = options.level_generator.generate(
+            rng.seed(),
+            game_config.starting_level,
+            &BrickGrid::default(),
+        );
+        if let Err(err) = layout.save(path) {
+            warn!("could not save level to {}: {}", path.display(), err);
+            std::process::exit(1);
+        }
+        println!(
+            "saved level {} of seed {} to {}",
+            game_config.starting_level,
+            rng.seed(),
+            path.display()
+        );
+        return;
+    }
+
+    let autoplayer = options.autoplayer(settings.bot);
+    let net_options = options.net_options();
+    let net_request = options.net_request();
+    let initial_state = if options.args.editor {
+        GameState::Editor
+    } else if net_request.is_some() {
+        GameState::Lobby
+    } else if options.skip_menu() {
+        GameState::InGame
+    } else {
+        GameState::MainMenu
+    };
+
+    app.insert_resource(WindowDescriptor {
+        width: options.args.width,
+        height: options.args.height,
+        title: "Breakout!!".to_string(),
+        mode: if options.args.fullscreen {
+            WindowMode::BorderlessFullscreen
+        } else {
+            settings.display_mode.window_mode()
+        },
+        resizable: true,
+        ..Default::default()
+    });
+
+    if options.args.headless {
+        app.add_plugin(HeadlessPlugin);
+        if let Some(seconds) = options.args.time_limit {
+            app.insert_resource(TimeLimit {
+                seconds,
+                elapsed: 0.0,
+            });
+        }
+    } else {
+        app.add_plugins_with(DefaultPlugins, |group| group.disable::<LogPlugin>())
+            .add_plugin(MenuPlugin)
+            .add_plugin(SettingsMenuPlugin)
+            .add_plugin(DisplayPlugin)
+            .add_plugin(SoundPlugin)
+            .add_plugin(ParticlesPlugin)
+            .add_plugin(BallTrailPlugin)
+            .add_plugin(CameraEffectsPlugin)
+            .add_plugin(ScorePopupsPlugin);
+
+        #[cfg(feature = "debug_overlay")]
+        app.add_plugin(DebugOverlayPlugin);
+    }
+
+    if let Some(path) = options.args.level_file.clone() {
+        app.insert_resource(LevelFile(path));
+    }
+
+    if let Some(path) = options.args.record.clone() {
+        app.insert_resource(ReplayRecorder {
+            path,
+            replay: Replay::new(rng.seed(), &game_config),
+        });
+    }
+
+    #[cfg(feature = "spectator")]
+    app.insert_resource(SpectatorPort(options.args.spectator_port))
+        .add_plugin(SpectatorPlugin);
+
+    if let Some(net_request) = net_request {
+        app.insert_resource(net_request);
+    }
+
+    // replays are someone else's game and headless runs are scripted, so neither counts for high
+    // scores
+    match options.replay {
+        Some(replay) => {
+            app.insert_resource(ReplayPlayer { replay, frame: 0 });
+        }
+        None if !options.args.headless => {
+            app.add_plugin(HighScoresPlugin);
+        }
+        None => {}
+    }
+
+    app.add_event::<StateChange>()
+        .insert_resource(options.level_generator)
+        .add_event::<MenuEvent>()
+        .insert_resource(rng)
+        .insert_resource(game_config)
+        .insert_resource(settings)
+        .insert_resource(net_options)
+        .insert_resource(autoplayer)
+        .add_plugin(SettingsPlugin)
+        .add_plugin(ThemePlugin)
+        .add_plugin(GamePlugin)
+        .add_plugin(StatePlugin)
+        .add_plugin(ReplayPlugin)
+        .add_plugin(NetcodePlugin)
+        .add_plugin(ScalingPlugin)
+        .add_state(initial_state)
+        .run();
+}
@@ -1,9 +1,13 @@
 use bevy::prelude::*;
 
 use crate::{
+    game::prelude::{Difficulty, GameConfig, GameMode},
+    netcode::{NetRequest, NetRole},
+    settings::cycle_value,
     state_plugin::StateChange,
     utilities::{
-        self, despawn_entities, MenuButtonAction, MenuEntity, MenuOptions, SelectedOption,
+        self, despawn_entities, MenuButtonAction, MenuEntity, MenuEvent, MenuOptions, MenuStyle,
+        SelectedOption, SelectionMarker,
     },
     GameState,
 };
@@ -16,7 +20,8 @@ impl Plugin for MenuPlugin {
             .add_system_set(
                 SystemSet::on_update(GameState::MainMenu)
                     .with_system(utilities::menu_interaction)
-                    .with_system(select_menu_item),
+                    .with_system(select_menu_item)
+                    .with_system(update_option_labels.after(select_menu_item)),
             )
             .add_system_set(
                 SystemSet::on_pause(GameState::MainMenu)
@@ -28,33 +33,115 @@ impl Plugin for MenuPlugin {
     }
 }
 
-fn render_menu(commands: Commands, asset_server: Res<AssetServer>, windows: Res<Windows>) {
+fn render_menu(commands: Commands, asset_server: Res<AssetServer>, game_config: Res<GameConfig>) {
     utilities::render_menu(
         commands,
         asset_server,
-        windows,
         MenuOptions {
             title: "Breakout!!",
-            play_text: "Start",
+            buttons: vec![
+                ("Start".to_string(), MenuButtonAction::Play),
+                (mode_label(game_config.mode), MenuButtonAction::Mode),
+                (
+                    difficulty_label(game_config.difficulty),
+                    MenuButtonAction::Difficulty,
+                ),
+                ("Host game".to_string(), MenuButtonAction::Host),
+                ("Level editor".to_string(), MenuButtonAction::Editor),
+                ("Settings".to_string(), MenuButtonAction::Settings),
+                ("Quit".to_string(), MenuButtonAction::Quit),
+            ],
+            style: MenuStyle::MEDIUM,
         },
     );
 }
 
+fn mode_label(mode: GameMode) -> String {
+    format!("Mode: {}", mode.name())
+}
+
+fn difficulty_label(difficulty: Difficulty) -> String {
+    format!("Difficulty: {}", difficulty.name())
+}
+
 fn select_menu_item(
+    mut commands: Commands,
     keyboard_input: ResMut<Input<KeyCode>>,
     selected_option_query: Query<&MenuButtonAction, With<SelectedOption>>,
+    mut game_config: ResMut<GameConfig>,
     mut event_state_change: EventWriter<StateChange>,
+    mut menu_events: EventWriter<MenuEvent>,
 ) {
     let menu_action = selected_option_query.single();
 
-    if keyboard_input.just_pressed(KeyCode::Return) {
+    // the mode and difficulty are picked with left and right, like the settings
+    if let MenuButtonAction::Mode | MenuButtonAction::Difficulty = menu_action {
+        let step = if keyboard_input.just_pressed(KeyCode::Left) {
+            -1
+        } else if keyboard_input.just_pressed(KeyCode::Right)
+            || utilities::confirm_pressed(&keyboard_input)
+        {
+            1
+        } else {
+            return;
+        };
+
+        if let MenuButtonAction::Mode = menu_action {
+            game_config.mode = cycle_value(&GameMode::ALL, game_config.mode, step);
+        } else {
+            game_config.difficulty = cycle_value(&Difficulty::ALL, game_config.difficulty, step);
+        }
+        menu_events.send(MenuEvent::Selected);
+        return;
+    }
+
+    if utilities::confirm_pressed(&keyboard_input) {
+        menu_events.send(MenuEvent::Selected);
         match menu_action {
             MenuButtonAction::Play => {
                 event_state_change.send(StateChange::Set(GameState::InGame));
             }
+            // joining needs the host's address, which only the command line (`--join`) takes
+            MenuButtonAction::Host => {
+                commands.insert_resource(NetRequest {
+                    role: NetRole::Host,
+                    exit_on_failure: false,
+                });
+                event_state_change.send(StateChange::Set(GameState::Lobby));
+            }
+            MenuButtonAction::Editor => {
+                event_state_change.send(StateChange::Set(GameState::Editor));
+            }
+            MenuButtonAction::Settings => {
+                event_state_change.send(StateChange::Set(GameState::Settings));
+            }
             MenuButtonAction::Quit => {
                 event_state_change.send(StateChange::Exit);
             }
+            _ => {}
+        }
+    }
+}
+
+fn update_option_labels(
+    game_config: Res<GameConfig>,
+    buttons_query: Query<(&MenuButtonAction, &Children)>,
+    mut text_query: Query<&mut Text, Without<SelectionMarker>>,
+) {
+    if !game_config.is_changed() {
+        return;
+    }
+
+    for (menu_action, children) in buttons_query.iter() {
+        let label = match menu_action {
+            MenuButtonAction::Mode => mode_label(game_config.mode),
+            MenuButtonAction::Difficulty => difficulty_label(game_config.difficulty),
+            _ => continue,
+        };
+        for child in children.iter() {
+            if let Ok(mut text) = text_query.get_mut(*child) {
+                text.sections[0].value = label.clone();
+            }
         }
     }
 }
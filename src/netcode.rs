@@ -0,0 +1,841 @@
+use std::{
+    io::ErrorKind,
+    net::{SocketAddr, ToSocketAddrs, UdpSocket},
+};
+
+use bevy::{
+    input::{keyboard::KeyboardInput, ElementState, InputSystem},
+    prelude::*,
+};
+use clap::ValueEnum;
+
+use crate::{
+    game::prelude::{
+        Difficulty, DifficultyParams, GameClock, GameConfig, GameMode, GameRng, SpeedUpConfig,
+        PADDLE_KEYS,
+    },
+    scaling::ScaledFont,
+    state_plugin::StateChange,
+    utilities::{despawn_entities, TEXT_COLOR},
+    GameState,
+};
+
+pub const DEFAULT_PORT: u16 = 7777;
+// Both games step by exactly this much on every frame they both have the inputs for
+const TIME_STEP: f32 = 1.0 / 60.0;
+// Real time that may be caught up on at once after a slow frame
+const MAX_TIME_BANK: f32 = 4.0 * TIME_STEP;
+const HELLO_INTERVAL: f32 = 0.25;
+// The joining side gives up when the host doesn't answer within this long
+const CONNECT_TIMEOUT: f32 = 10.0;
+// The game ends when nothing has been heard from the other player for this long
+const DISCONNECT_TIMEOUT: f32 = 5.0;
+// After the game, how long to keep sending inputs the other player may still be missing
+const LINGER_TIME: f32 = 2.0;
+// Stalls shorter than this are normal jitter and not worth a message
+const STALL_NOTICE: f32 = 0.25;
+const MAX_INPUTS_PER_PACKET: usize = 120;
+const MAX_PACKET_SIZE: usize = 1024;
+const STATUS_FONT_SIZE: f32 = 36.0;
+
+// Whether this side waits for a player or joins one
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum NetRole {
+    Host,
+    Join,
+}
+
+// Present from picking host or join until the network game starts (or fails to)
+pub struct NetRequest {
+    pub role: NetRole,
+    // with no menu to go back to, failing to connect exits the game
+    pub exit_on_failure: bool,
+}
+
+// Where to host or join, from the command line
+pub struct NetOptions {
+    pub port: u16,
+    // `host:port` or just the host, which then uses `port`
+    pub address: String,
+    // frames between pressing a key and the paddle moving, which hides that much latency
+    pub input_delay: usize,
+    // picked at random by the host when omitted
+    pub seed: Option<u64>,
+}
+
+impl Default for NetOptions {
+    fn default() -> Self {
+        NetOptions {
+            port: DEFAULT_PORT,
+            address: "127.0.0.1".to_string(),
+            input_delay: 3,
+            seed: None,
+        }
+    }
+}
+
+impl NetOptions {
+    fn host_address(&self) -> Option<SocketAddr> {
+        let address = if self.address.contains(':') {
+            self.address.clone()
+        } else {
+            format!("{}:{}", self.address, self.port)
+        };
+        // the socket is bound to IPv4, so the host has to be reachable over it
+        address
+            .to_socket_addrs()
+            .ok()?
+            .find(|address| address.is_ipv4())
+    }
+}
+
+// Whether the left and right keys are held
+type Keys = (bool, bool);
+
+// Everything both sides need to set up the same game, sent by the host
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct GameSetup {
+    seed: u64,
+    level: u32,
+    difficulty: Difficulty,
+    custom_difficulty: DifficultyParams,
+    speed_up: SpeedUpConfig,
+}
+
+impl GameSetup {
+    fn apply(&self, rng: &mut GameRng, game_config: &mut GameConfig) {
+        *rng = GameRng::new(self.seed);
+        *game_config = GameConfig {
+            mode: GameMode::Versus,
+            starting_level: self.level,
+            difficulty: self.difficulty,
+            custom_difficulty: self.custom_difficulty,
+            layout: None,
+            speed_up: self.speed_up,
+        };
+    }
+}
+
+// The packets sent back and forth, one line of text each:
+//   `hello` - the joining side asking to play
+//   `welcome <seed> <level> <difficulty> <custom difficulty> <speed up>` - the host accepting
+//   `inputs <ack> <first frame> <keys>` - the sender's inputs from a frame on, one character per
+//     frame, plus how many of the receiver's inputs it has
+//   `bye` - the sender has left
+#[derive(Debug, Clone, PartialEq)]
+enum Message {
+    Hello,
+    Welcome(GameSetup),
+    Inputs {
+        ack: usize,
+        first_frame: usize,
+        keys: Vec<Keys>,
+    },
+    Bye,
+}
+
+impl Message {
+    fn encode(&self) -> String {
+        match self {
+            Message::Hello => "hello".to_string(),
+            Message::Welcome(setup) => {
+                let difficulty = setup.difficulty.to_possible_value().unwrap();
+                let custom = &setup.custom_difficulty;
+                let speed_up = &setup.speed_up;
+                format!(
+                    "welcome {} {} {} {} {} {} {} {} {} {} {} {} {} {}",
+                    setup.seed,
+                    setup.level,
+                    difficulty.get_name(),
+                    custom.ball_speed,
+                    custom.ball_acceleration,
+                    custom.paddle_width,
+                    custom.paddle_speed,
+                    custom.lives,
+                    custom.score_multiplier,
+                    speed_up.paddle_hits_per_level,
+                    speed_up.seconds_per_level,
+                    speed_up.top_row,
+                    speed_up.step,
+                    speed_up.max_level
+                )
+            }
+            Message::Inputs {
+                ack,
+                first_frame,
+                keys,
+            } => {
+                let keys: String = keys
+                    .iter()
+                    .map(|(left, right)| (b'0' + *left as u8 + ((*right as u8) << 1)) as char)
+                    .collect();
+                format!("inputs {} {} {}", ack, first_frame, keys)
+            }
+            Message::Bye => "bye".to_string(),
+        }
+    }
+
+    fn decode(packet: &str) -> Option<Message> {
+        let mut fields = packet.split_whitespace();
+        match fields.next()? {
+            "hello" => Some(Message::Hello),
+            "welcome" => {
+                let values: Vec<&str> = fields.collect();
+                if values.len() != 14 {
+                    return None;
+                }
+                let float = |index: usize| values[index].parse::<f32>().ok();
+                Some(Message::Welcome(GameSetup {
+                    seed: values[0].parse().ok()?,
+                    // levels start at 1, anything else would break the game setting up
+                    level: values[1].parse().ok().filter(|level| *level >= 1)?,
+                    difficulty: Difficulty::from_str(values[2], true).ok()?,
+                    custom_difficulty: DifficultyParams {
+                        ball_speed: float(3)?,
+                        ball_acceleration: float(4)?,
+                        paddle_width: float(5)?,
+                        paddle_speed: float(6)?,
+                        lives: values[7].parse().ok()?,
+                        score_multiplier: float(8)?,
+                    },
+                    speed_up: SpeedUpConfig {
+                        paddle_hits_per_level: values[9].parse().ok()?,
+                        seconds_per_level: float(10)?,
+                        top_row: values[11].parse().ok()?,
+                        step: float(12)?,
+                        max_level: values[13].parse().ok()?,
+                    },
+                }))
+            }
+            "inputs" => {
+                let ack = fields.next()?.parse().ok()?;
+                let first_frame = fields.next()?.parse().ok()?;
+                let keys = fields
+                    .next()
+                    .unwrap_or("")
+                    .bytes()
+                    .map(|key| {
+                        let bits = key.checked_sub(b'0').filter(|bits| *bits < 4)?;
+                        Some((bits & 1 != 0, bits & 2 != 0))
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(Message::Inputs {
+                    ack,
+                    first_frame,
+                    keys,
+                })
+            }
+            "bye" => Some(Message::Bye),
+            _ => None,
+        }
+    }
+}
+
+// The connection to the other player, from the lobby until a little after the game is over.
+//
+// The games run in lockstep: a game frame is only simulated once the inputs of both players for
+// it are known, and every frame steps the game by the same fixed time. With the same seed and
+// settings that keeps both games identical, so only the inputs ever need to be sent. Local inputs
+// are scheduled a few frames ahead, which gives them time to arrive before they're needed.
+pub struct NetSession {
+    socket: UdpSocket,
+    role: NetRole,
+    exit_on_failure: bool,
+    peer: Option<SocketAddr>,
+    setup: Option<GameSetup>,
+    // the host plays at the bottom, the joining player at the top
+    local_player: usize,
+    input_delay: usize,
+    // the next game frame to simulate
+    frame: usize,
+    local_inputs: Vec<Keys>,
+    remote_inputs: Vec<Keys>,
+    // how many of the local inputs the other player has received
+    peer_has: usize,
+    held: Keys,
+    time_bank: f32,
+    since_heard: f32,
+    since_hello: f32,
+    stalled_for: f32,
+    peer_left: bool,
+    // time since the game ended, while making sure the other player has every input
+    finished_for: Option<f32>,
+}
+
+impl NetSession {
+    fn open(request: &NetRequest, options: &NetOptions) -> Result<Self, String> {
+        let (bind_address, peer) = match request.role {
+            NetRole::Host => (SocketAddr::from(([0, 0, 0, 0], options.port)), None),
+            NetRole::Join => {
+                let peer = options
+                    .host_address()
+                    .ok_or_else(|| format!("could not resolve '{}'", options.address))?;
+                (SocketAddr::from(([0, 0, 0, 0], 0)), Some(peer))
+            }
+        };
+        let socket = UdpSocket::bind(bind_address)
+            .and_then(|socket| socket.set_nonblocking(true).map(|_| socket))
+            .map_err(|err| format!("could not open port {}: {}", bind_address.port(), err))?;
+
+        Ok(NetSession {
+            socket,
+            role: request.role,
+            exit_on_failure: request.exit_on_failure,
+            peer,
+            setup: None,
+            local_player: match request.role {
+                NetRole::Host => 0,
+                NetRole::Join => 1,
+            },
+            input_delay: options.input_delay,
+            frame: 0,
+            local_inputs: Vec::new(),
+            remote_inputs: Vec::new(),
+            peer_has: 0,
+            held: (false, false),
+            time_bank: 0.0,
+            since_heard: 0.0,
+            // say hello straight away
+            since_hello: HELLO_INTERVAL,
+            stalled_for: 0.0,
+            peer_left: false,
+            finished_for: None,
+        })
+    }
+
+    fn send(&self, message: &Message) {
+        if let Some(peer) = self.peer {
+            // a lost packet is no different from one dropped on the way
+            let _ = self.socket.send_to(message.encode().as_bytes(), peer);
+        }
+    }
+
+    // Every message that arrived since the last call, with who sent it
+    fn receive(&mut self) -> Vec<(SocketAddr, Message)> {
+        let mut messages = Vec::new();
+        let mut buffer = [0; MAX_PACKET_SIZE];
+        loop {
+            match self.socket.recv_from(&mut buffer) {
+                Ok((length, sender)) => {
+                    // only the other player is listened to once there is one
+                    if self.peer.is_some_and(|peer| peer != sender) {
+                        continue;
+                    }
+                    let message = std::str::from_utf8(&buffer[..length])
+                        .ok()
+                        .and_then(Message::decode);
+                    if let Some(message) = message {
+                        self.since_heard = 0.0;
+                        messages.push((sender, message));
+                    }
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                // e.g. the other side's port being closed, which the timeout takes care of
+                Err(_) => break,
+            }
+        }
+        messages
+    }
+
+    fn add_remote_inputs(&mut self, ack: usize, first_frame: usize, keys: Vec<Keys>) {
+        self.peer_has = self.peer_has.max(ack);
+        // inputs always arrive from the first one missing, so anything else is old
+        for (frame, keys) in (first_frame..).zip(keys) {
+            if frame == self.remote_inputs.len() {
+                self.remote_inputs.push(keys);
+            }
+        }
+    }
+
+    fn send_inputs(&self) {
+        let first_frame = self.peer_has.min(self.local_inputs.len());
+        let last_frame = self
+            .local_inputs
+            .len()
+            .min(first_frame + MAX_INPUTS_PER_PACKET);
+        self.send(&Message::Inputs {
+            ack: self.remote_inputs.len(),
+            first_frame,
+            keys: self.local_inputs[first_frame..last_frame].to_vec(),
+        });
+    }
+
+    // Whether the game is waiting on the other player's inputs
+    fn is_stalled(&self) -> bool {
+        self.remote_inputs.len() <= self.frame
+    }
+}
+
+#[derive(Component)]
+struct LobbyEntity;
+
+#[derive(Component)]
+struct WaitingText;
+
+// Versus between two instances of the game over UDP. One player hosts, the other joins with
+// the host's address, and the host's settings are used for the game.
+pub struct NetcodePlugin;
+
+impl Plugin for NetcodePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NetOptions>()
+            .add_system_set(SystemSet::on_enter(GameState::Lobby).with_system(open_lobby))
+            .add_system_set(SystemSet::on_update(GameState::Lobby).with_system(connect))
+            .add_system_set(
+                SystemSet::on_exit(GameState::Lobby).with_system(despawn_entities::<LobbyEntity>),
+            )
+            .add_system_to_stage(CoreStage::PreUpdate, exchange_inputs.after(InputSystem))
+            .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(render_waiting_text))
+            .add_system_set(
+                SystemSet::on_update(GameState::InGame).with_system(update_waiting_text),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::InGame).with_system(despawn_entities::<WaitingText>),
+            );
+    }
+}
+
+fn open_lobby(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    request: Option<Res<NetRequest>>,
+    options: Res<NetOptions>,
+    mut event_state_change: EventWriter<StateChange>,
+) {
+    let request = match request {
+        Some(request) => request,
+        None => return,
+    };
+
+    let session = match NetSession::open(&request, &options) {
+        Ok(session) => session,
+        Err(err) => {
+            warn!("{}", err);
+            event_state_change.send(leave_lobby(request.exit_on_failure));
+            return;
+        }
+    };
+    let status = match request.role {
+        NetRole::Host => format!(
+            "Waiting for another player\nto join on port {}",
+            options.port
+        ),
+        NetRole::Join => format!("Joining {}", options.address),
+    };
+    info!("{}", status.replace('\n', " "));
+    commands.insert_resource(session);
+    commands.remove_resource::<NetRequest>();
+
+    commands
+        .spawn_bundle(UiCameraBundle::default())
+        .insert(LobbyEntity);
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                margin: Rect::all(Val::Auto),
+                ..default()
+            },
+            text: Text::with_section(
+                format!("{}...\n\nEsc to cancel", status),
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: STATUS_FONT_SIZE,
+                    color: TEXT_COLOR,
+                },
+                TextAlignment {
+                    horizontal: HorizontalAlign::Center,
+                    ..default()
+                },
+            ),
+            ..default()
+        })
+        .insert(ScaledFont(STATUS_FONT_SIZE))
+        .insert(LobbyEntity);
+}
+
+fn leave_lobby(exit: bool) -> StateChange {
+    if exit {
+        StateChange::Exit
+    } else {
+        StateChange::Set(GameState::MainMenu)
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn connect(
+    mut commands: Commands,
+    session: Option<ResMut<NetSession>>,
+    options: Res<NetOptions>,
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut rng: ResMut<GameRng>,
+    mut game_config: ResMut<GameConfig>,
+    mut event_state_change: EventWriter<StateChange>,
+) {
+    // the session is opened when entering the lobby
+    let mut session = match session {
+        Some(session) => session,
+        None => return,
+    };
+
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        session.send(&Message::Bye);
+        commands.remove_resource::<NetSession>();
+        event_state_change.send(StateChange::Set(GameState::MainMenu));
+        return;
+    }
+
+    // waiting for the game to start
+    if session.setup.is_some() {
+        return;
+    }
+
+    for (sender, message) in session.receive() {
+        match (session.role, message) {
+            (NetRole::Host, Message::Hello) => {
+                let setup = GameSetup {
+                    seed: options.seed.unwrap_or_else(rand::random),
+                    level: game_config.starting_level,
+                    difficulty: game_config.difficulty,
+                    custom_difficulty: game_config.custom_difficulty,
+                    speed_up: game_config.speed_up,
+                };
+                info!("{} joined the game", sender);
+                session.peer = Some(sender);
+                session.setup = Some(setup);
+                session.send(&Message::Welcome(setup));
+            }
+            (NetRole::Join, Message::Welcome(setup)) => {
+                info!("joined the game");
+                session.setup = Some(setup);
+            }
+            _ => continue,
+        }
+
+        if let Some(setup) = session.setup {
+            setup.apply(&mut rng, &mut game_config);
+            event_state_change.send(StateChange::Set(GameState::InGame));
+            return;
+        }
+    }
+
+    if session.role == NetRole::Join {
+        session.since_hello += time.delta_seconds();
+        if session.since_hello >= HELLO_INTERVAL {
+            session.since_hello = 0.0;
+            session.send(&Message::Hello);
+        }
+
+        session.since_heard += time.delta_seconds();
+        if session.since_heard > CONNECT_TIMEOUT {
+            warn!("no answer from {}", options.address);
+            let exit = session.exit_on_failure;
+            commands.remove_resource::<NetSession>();
+            event_state_change.send(leave_lobby(exit));
+        }
+    }
+}
+
+// Runs the lockstep: sends the local inputs, and lets the game step only once both players'
+// inputs for the frame are in, pressing their paddle keys as the inputs say
+#[allow(clippy::too_many_arguments)]
+fn exchange_inputs(
+    mut commands: Commands,
+    session: Option<ResMut<NetSession>>,
+    mut app_state: ResMut<State<GameState>>,
+    time: Res<Time>,
+    mut clock: ResMut<GameClock>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    mut keyboard_events: EventReader<KeyboardInput>,
+) {
+    let mut session = match session {
+        Some(session) => session,
+        None => return,
+    };
+    // the lobby has the session to itself until the game starts
+    if session.setup.is_none() {
+        return;
+    }
+    if *app_state.current() == GameState::Lobby {
+        // the game's first frame runs as the state changes, before there are inputs for it
+        clock.set_delta(0.0);
+        return;
+    }
+
+    // the local player always plays with the arrow keys, whichever paddle is theirs
+    let (left_key, right_key) = PADDLE_KEYS[0];
+    for event in keyboard_events.iter() {
+        let pressed = event.state == ElementState::Pressed;
+        if event.key_code == Some(left_key) {
+            session.held.0 = pressed;
+        } else if event.key_code == Some(right_key) {
+            session.held.1 = pressed;
+        }
+    }
+
+    for (_, message) in session.receive() {
+        match message {
+            Message::Inputs {
+                ack,
+                first_frame,
+                keys,
+            } => session.add_remote_inputs(ack, first_frame, keys),
+            Message::Bye => session.peer_left = true,
+            _ => {}
+        }
+    }
+    session.since_heard += time.delta_seconds();
+
+    if *app_state.current() == GameState::InGame {
+        // pausing one game would stall the other
+        keyboard_input.clear_just_pressed(KeyCode::Escape);
+
+        let lost = session.since_heard > DISCONNECT_TIMEOUT;
+        if lost || (session.peer_left && session.is_stalled()) {
+            warn!("the other player left the game");
+            release_paddle_keys(&mut keyboard_input);
+            commands.remove_resource::<NetSession>();
+            let _ = app_state.set(GameState::GameOver);
+            return;
+        }
+
+        step_game(&mut session, &time, &mut clock, &mut keyboard_input);
+
+        // until the joining side has started, it may not have got the welcome
+        if session.role == NetRole::Host && session.remote_inputs.is_empty() {
+            if let Some(setup) = session.setup {
+                session.send(&Message::Welcome(setup));
+            }
+        }
+        session.send_inputs();
+        return;
+    }
+
+    // the game is over, make sure the other player can get there too before leaving
+    if session.finished_for.is_none() {
+        release_paddle_keys(&mut keyboard_input);
+    }
+    let finished_for = session.finished_for.unwrap_or(0.0) + time.delta_seconds();
+    session.finished_for = Some(finished_for);
+    let delivered = session.peer_has >= session.local_inputs.len();
+    if delivered || session.peer_left || finished_for > LINGER_TIME {
+        session.send(&Message::Bye);
+        commands.remove_resource::<NetSession>();
+    } else {
+        session.send_inputs();
+    }
+}
+
+fn step_game(
+    session: &mut NetSession,
+    time: &Time,
+    clock: &mut GameClock,
+    keyboard_input: &mut Input<KeyCode>,
+) {
+    // game frames are paced by real time, so a fast display doesn't speed the game up
+    session.time_bank = (session.time_bank + time.delta_seconds()).min(MAX_TIME_BANK);
+    if session.time_bank < TIME_STEP {
+        clock.set_delta(0.0);
+        return;
+    }
+
+    let held = session.held;
+    while session.local_inputs.len() <= session.frame + session.input_delay {
+        session.local_inputs.push(held);
+    }
+
+    let remote = match session.remote_inputs.get(session.frame) {
+        Some(remote) => *remote,
+        None => {
+            session.stalled_for += time.delta_seconds();
+            clock.set_delta(0.0);
+            return;
+        }
+    };
+
+    let mut inputs = [remote; 2];
+    inputs[session.local_player] = session.local_inputs[session.frame];
+    for ((left_key, right_key), (left, right)) in PADDLE_KEYS.into_iter().zip(inputs) {
+        for (key, pressed) in [(left_key, left), (right_key, right)] {
+            if pressed {
+                keyboard_input.press(key);
+            } else {
+                keyboard_input.release(key);
+            }
+        }
+    }
+
+    session.frame += 1;
+    session.time_bank -= TIME_STEP;
+    session.stalled_for = 0.0;
+    clock.set_delta(TIME_STEP);
+}
+
+fn release_paddle_keys(keyboard_input: &mut Input<KeyCode>) {
+    for (left_key, right_key) in PADDLE_KEYS {
+        keyboard_input.release(left_key);
+        keyboard_input.release(right_key);
+    }
+}
+
+fn render_waiting_text(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    session: Option<Res<NetSession>>,
+) {
+    if session.is_none() {
+        return;
+    }
+
+    commands
+        .spawn_bundle(TextBundle {
+            style: Style {
+                margin: Rect::all(Val::Auto),
+                ..default()
+            },
+            text: Text::with_section(
+                "Waiting for the other player...",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: STATUS_FONT_SIZE,
+                    color: TEXT_COLOR,
+                },
+                default(),
+            ),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(ScaledFont(STATUS_FONT_SIZE))
+        .insert(WaitingText);
+}
+
+fn update_waiting_text(
+    session: Option<Res<NetSession>>,
+    mut text_query: Query<&mut Visibility, With<WaitingText>>,
+) {
+    let waiting = session.is_some_and(|session| session.stalled_for > STALL_NOTICE);
+    for mut visibility in text_query.iter_mut() {
+        visibility.is_visible = waiting;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use super::*;
+
+    fn setup() -> GameSetup {
+        GameSetup {
+            seed: 42,
+            level: 3,
+            difficulty: Difficulty::Custom,
+            custom_difficulty: DifficultyParams {
+                ball_speed: 1.5,
+                lives: 7,
+                ..DifficultyParams::default()
+            },
+            speed_up: SpeedUpConfig {
+                top_row: false,
+                step: 0.25,
+                ..SpeedUpConfig::default()
+            },
+        }
+    }
+
+    // Waits a little for packets, the sockets don't block
+    fn receive_some(session: &mut NetSession) -> Vec<(SocketAddr, Message)> {
+        let start = Instant::now();
+        loop {
+            let messages = session.receive();
+            if !messages.is_empty() || start.elapsed() > Duration::from_secs(2) {
+                return messages;
+            }
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn messages_survive_encoding() {
+        let messages = [
+            Message::Hello,
+            Message::Welcome(setup()),
+            Message::Inputs {
+                ack: 12,
+                first_frame: 9,
+                keys: vec![(false, false), (true, false), (false, true), (true, true)],
+            },
+            Message::Inputs {
+                ack: 0,
+                first_frame: 0,
+                keys: Vec::new(),
+            },
+            Message::Bye,
+        ];
+        for message in messages {
+            assert_eq!(Message::decode(&message.encode()), Some(message));
+        }
+    }
+
+    #[test]
+    fn broken_packets_are_ignored() {
+        for packet in [
+            "",
+            "hi",
+            "welcome 1 2 normal",
+            "welcome x 1 normal 1 0.1 120 600 3 1 6 20 true 0.1 5",
+            "welcome 7 0 normal 1 0.1 120 600 3 1 6 20 true 0.1 5",
+            "inputs 1",
+            "inputs 1 2 0124",
+        ] {
+            assert_eq!(Message::decode(packet), None, "{:?}", packet);
+        }
+        // the level 0 one above is only broken by its level
+        assert!(Message::decode("welcome 7 1 normal 1 0.1 120 600 3 1 6 20 true 0.1 5").is_some());
+    }
+
+    #[test]
+    fn sessions_talk_over_loopback() {
+        let mut options = NetOptions {
+            port: 0,
+            ..NetOptions::default()
+        };
+        let host_request = NetRequest {
+            role: NetRole::Host,
+            exit_on_failure: false,
+        };
+        let mut host = NetSession::open(&host_request, &options).unwrap();
+
+        options.port = host.socket.local_addr().unwrap().port();
+        let join_request = NetRequest {
+            role: NetRole::Join,
+            exit_on_failure: false,
+        };
+        let mut join = NetSession::open(&join_request, &options).unwrap();
+        assert_eq!(join.local_player, 1);
+
+        join.send(&Message::Hello);
+        let (sender, message) = receive_some(&mut host).remove(0);
+        assert_eq!(message, Message::Hello);
+
+        host.peer = Some(sender);
+        host.send(&Message::Welcome(setup()));
+        let (_, message) = receive_some(&mut join).remove(0);
+        assert_eq!(message, Message::Welcome(setup()));
+
+        host.local_inputs = vec![(true, false), (false, true)];
+        host.send_inputs();
+        let (_, message) = receive_some(&mut join).remove(0);
+        assert_eq!(
+            message,
+            Message::Inputs {
+                ack: 0,
+                first_frame: 0,
+                keys: vec![(true, false), (false, true)],
+            }
+        );
+    }
+}
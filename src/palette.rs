@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+// Colour schemes that replace the theme's colours, for players who can't tell some of them apart
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum Palette {
+    Standard,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+    HighContrast,
+}
+
+pub struct PaletteColors {
+    pub background: Color,
+    pub ball: Color,
+    pub paddle: Color,
+    pub wall: Color,
+    pub brick: Color,
+    pub strong_brick: Color,
+    pub steel_brick: Color,
+    pub time_brick: Color,
+}
+
+impl Palette {
+    pub const ALL: [Palette; 5] = [
+        Palette::Standard,
+        Palette::Deuteranopia,
+        Palette::Protanopia,
+        Palette::Tritanopia,
+        Palette::HighContrast,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Palette::Standard => "Standard",
+            Palette::Deuteranopia => "Deuteranopia",
+            Palette::Protanopia => "Protanopia",
+            Palette::Tritanopia => "Tritanopia",
+            Palette::HighContrast => "High contrast",
+        }
+    }
+
+    // The standard palette keeps the theme's own colours. The others are built from the
+    // Okabe-Ito colours, picking pairs that stay apart for each kind of colour blindness.
+    pub fn colors(&self) -> Option<PaletteColors> {
+        match self {
+            Palette::Standard => None,
+            // red-green, with green looking weaker
+            Palette::Deuteranopia => Some(PaletteColors {
+                background: Color::rgb(0.12, 0.12, 0.12),
+                ball: Color::rgb(0.95, 0.9, 0.25),
+                paddle: Color::rgb(0.35, 0.7, 0.9),
+                wall: Color::rgb(0.8, 0.8, 0.8),
+                brick: Color::rgb(0.0, 0.45, 0.7),
+                strong_brick: Color::rgb(0.9, 0.6, 0.0),
+                steel_brick: Color::rgb(0.55, 0.55, 0.55),
+                time_brick: Color::rgb(0.8, 0.47, 0.65),
+            }),
+            // red-green, with red looking darker
+            Palette::Protanopia => Some(PaletteColors {
+                background: Color::rgb(0.12, 0.12, 0.12),
+                ball: Color::WHITE,
+                paddle: Color::rgb(0.0, 0.45, 0.7),
+                wall: Color::rgb(0.8, 0.8, 0.8),
+                brick: Color::rgb(0.35, 0.7, 0.9),
+                strong_brick: Color::rgb(0.95, 0.75, 0.1),
+                steel_brick: Color::rgb(0.5, 0.5, 0.5),
+                time_brick: Color::rgb(0.8, 0.47, 0.65),
+            }),
+            // blue-yellow
+            Palette::Tritanopia => Some(PaletteColors {
+                background: Color::rgb(0.12, 0.12, 0.12),
+                ball: Color::WHITE,
+                paddle: Color::rgb(0.8, 0.6, 0.7),
+                wall: Color::rgb(0.8, 0.8, 0.8),
+                brick: Color::rgb(0.0, 0.6, 0.5),
+                strong_brick: Color::rgb(0.85, 0.25, 0.4),
+                steel_brick: Color::rgb(0.55, 0.55, 0.55),
+                time_brick: Color::rgb(0.95, 0.9, 0.25),
+            }),
+            Palette::HighContrast => Some(PaletteColors {
+                background: Color::BLACK,
+                ball: Color::rgb(1.0, 1.0, 0.0),
+                paddle: Color::WHITE,
+                wall: Color::WHITE,
+                brick: Color::rgb(0.0, 0.85, 1.0),
+                strong_brick: Color::rgb(1.0, 0.4, 0.9),
+                steel_brick: Color::rgb(0.6, 0.6, 0.6),
+                time_brick: Color::rgb(0.2, 1.0, 0.2),
+            }),
+        }
+    }
+}
@@ -0,0 +1,473 @@
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+use bevy::{app::AppExit, ecs::schedule::SystemLabel, input::InputSystem, prelude::*};
+use clap::ValueEnum;
+
+use crate::{
+    game::prelude::{
+        BrickGrid, Difficulty, DifficultyParams, GameClock, GameConfig, GameMode, GameRng,
+        LevelLayout, SpeedUpConfig, PADDLE_KEYS,
+    },
+    GameState,
+};
+
+const REPLAY_HEADER: &str = "breakout-replay 1";
+
+// The letters standing for every player's left and right key in replay files
+const KEY_LETTERS: [(char, char); 2] = [('L', 'R'), ('A', 'D')];
+
+// A recorded game: how it was set up plus the paddle input and time step of every game frame.
+// Playing it back with the same seed re-runs the exact same game.
+pub struct Replay {
+    // only saved for modes other than classic
+    pub mode: GameMode,
+    pub seed: u64,
+    pub level: u32,
+    pub difficulty: Difficulty,
+    // only saved when the difficulty is `Custom`
+    pub custom_difficulty: DifficultyParams,
+    // only saved when it isn't the default
+    pub speed_up: SpeedUpConfig,
+    // only saved when the game started on a level from a file or the editor
+    pub layout: Option<LevelLayout>,
+    pub frames: Vec<ReplayFrame>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReplayFrame {
+    pub delta: f32,
+    // whether every player's left and right keys are held, see `PADDLE_KEYS`
+    pub keys: [(bool, bool); 2],
+}
+
+impl Replay {
+    pub fn new(seed: u64, game_config: &GameConfig) -> Self {
+        Replay {
+            mode: game_config.mode,
+            seed,
+            level: game_config.starting_level,
+            difficulty: game_config.difficulty,
+            custom_difficulty: game_config.custom_difficulty,
+            speed_up: game_config.speed_up,
+            layout: game_config.layout.clone(),
+            frames: Vec::new(),
+        }
+    }
+
+    pub fn load(path: &Path) -> io::Result<Replay> {
+        Replay::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.contents())
+    }
+
+    fn parse(contents: &str) -> io::Result<Replay> {
+        let mut lines = contents.lines().peekable();
+
+        if lines.next() != Some(REPLAY_HEADER) {
+            return Err(invalid_data("missing replay header"));
+        }
+
+        let seed = parse_field(lines.next(), "seed")?;
+        let level = parse_field(lines.next(), "level")?;
+        if level < 1 {
+            return Err(invalid_data("level must be at least 1"));
+        }
+        let difficulty = match lines
+            .next()
+            .and_then(|line| line.strip_prefix("difficulty "))
+        {
+            Some(name) => Difficulty::from_str(name, true).map_err(invalid_data)?,
+            None => return Err(invalid_data("missing difficulty")),
+        };
+        let custom_difficulty = if difficulty == Difficulty::Custom {
+            parse_custom_difficulty(lines.next())?
+        } else {
+            DifficultyParams::default()
+        };
+
+        let mode = match lines.peek().and_then(|line| line.strip_prefix("mode ")) {
+            Some(name) => {
+                let mode = GameMode::from_str(name, true).map_err(invalid_data)?;
+                lines.next();
+                mode
+            }
+            None => GameMode::Classic,
+        };
+
+        let speed_up = match lines.peek().filter(|line| line.starts_with("speed_up ")) {
+            Some(line) => {
+                let speed_up = parse_speed_up(line)?;
+                lines.next();
+                speed_up
+            }
+            None => SpeedUpConfig::default(),
+        };
+
+        // `layout <row>` for every row of the level's bricks, from the top
+        let mut layout_rows = Vec::new();
+        while let Some(row) = lines
+            .peek()
+            .and_then(|line| line.strip_prefix("layout "))
+            .map(str::to_string)
+        {
+            layout_rows.push(row);
+            lines.next();
+        }
+        let layout = if layout_rows.is_empty() {
+            None
+        } else {
+            // held to the same rules as level files
+            let layout = LevelLayout { rows: layout_rows };
+            layout
+                .validate(&BrickGrid::default())
+                .map_err(|err| invalid_data(format!("invalid layout: {}", err)))?;
+            Some(layout)
+        };
+
+        let frames = lines
+            .filter(|line| !line.is_empty())
+            .map(parse_frame)
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(Replay {
+            mode,
+            seed,
+            level,
+            difficulty,
+            custom_difficulty,
+            speed_up,
+            layout,
+            frames,
+        })
+    }
+
+    fn contents(&self) -> String {
+        let difficulty = self.difficulty.to_possible_value().unwrap();
+        let mut contents = format!(
+            "{}\nseed {}\nlevel {}\ndifficulty {}\n",
+            REPLAY_HEADER,
+            self.seed,
+            self.level,
+            difficulty.get_name()
+        );
+        if self.difficulty == Difficulty::Custom {
+            let custom = &self.custom_difficulty;
+            contents.push_str(&format!(
+                "custom {} {} {} {} {} {}\n",
+                custom.ball_speed,
+                custom.ball_acceleration,
+                custom.paddle_width,
+                custom.paddle_speed,
+                custom.lives,
+                custom.score_multiplier
+            ));
+        }
+        if self.mode != GameMode::Classic {
+            let mode = self.mode.to_possible_value().unwrap();
+            contents.push_str(&format!("mode {}\n", mode.get_name()));
+        }
+        if self.speed_up != SpeedUpConfig::default() {
+            let speed_up = &self.speed_up;
+            contents.push_str(&format!(
+                "speed_up {} {} {} {} {}\n",
+                speed_up.paddle_hits_per_level,
+                speed_up.seconds_per_level,
+                speed_up.top_row,
+                speed_up.step,
+                speed_up.max_level
+            ));
+        }
+        if let Some(layout) = &self.layout {
+            for row in layout.rows.iter() {
+                contents.push_str(&format!("layout {}\n", row));
+            }
+        }
+
+        // only two-player games have keys for the second player
+        let players = self.mode.players();
+        for frame in self.frames.iter() {
+            let keys: String = frame.keys[..players]
+                .iter()
+                .zip(KEY_LETTERS)
+                .flat_map(|((left, right), (left_letter, right_letter))| {
+                    [
+                        if *left { left_letter } else { '-' },
+                        if *right { right_letter } else { '-' },
+                    ]
+                })
+                .collect();
+            contents.push_str(&format!("{} {}\n", frame.delta, keys));
+        }
+        contents
+    }
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message.into())
+}
+
+fn parse_field<T: std::str::FromStr>(line: Option<&str>, name: &str) -> io::Result<T> {
+    line.and_then(|line| line.strip_prefix(name))
+        .and_then(|value| value.trim().parse().ok())
+        .ok_or_else(|| invalid_data(format!("missing or invalid {}", name)))
+}
+
+// `custom <ball speed> <ball acceleration> <paddle width> <paddle speed> <lives> <score multiplier>`
+fn parse_custom_difficulty(line: Option<&str>) -> io::Result<DifficultyParams> {
+    let invalid = || invalid_data("missing or invalid custom difficulty");
+    let values: Vec<&str> = line
+        .and_then(|line| line.strip_prefix("custom "))
+        .ok_or_else(invalid)?
+        .split_whitespace()
+        .collect();
+    if values.len() != 6 {
+        return Err(invalid());
+    }
+
+    let float = |index: usize| values[index].parse::<f32>().map_err(|_| invalid());
+    Ok(DifficultyParams {
+        ball_speed: float(0)?,
+        ball_acceleration: float(1)?,
+        paddle_width: float(2)?,
+        paddle_speed: float(3)?,
+        lives: values[4].parse().map_err(|_| invalid())?,
+        score_multiplier: float(5)?,
+    })
+}
+
+// `speed_up <paddle hits per level> <seconds per level> <top row> <step> <max level>`
+fn parse_speed_up(line: &str) -> io::Result<SpeedUpConfig> {
+    let invalid = || invalid_data("invalid speed up");
+    let values: Vec<&str> = line.split_whitespace().skip(1).collect();
+    if values.len() != 5 {
+        return Err(invalid());
+    }
+
+    Ok(SpeedUpConfig {
+        paddle_hits_per_level: values[0].parse().map_err(|_| invalid())?,
+        seconds_per_level: values[1].parse().map_err(|_| invalid())?,
+        top_row: values[2].parse().map_err(|_| invalid())?,
+        step: values[3].parse().map_err(|_| invalid())?,
+        max_level: values[4].parse().map_err(|_| invalid())?,
+    })
+}
+
+fn parse_frame(line: &str) -> io::Result<ReplayFrame> {
+    let invalid_frame = || invalid_data(format!("invalid frame '{}'", line));
+    let (delta, keys) = line.split_once(' ').ok_or_else(invalid_frame)?;
+
+    Ok(ReplayFrame {
+        delta: delta.parse().map_err(|_| invalid_frame())?,
+        keys: KEY_LETTERS.map(|(left, right)| (keys.contains(left), keys.contains(right))),
+    })
+}
+
+// Present while the session is being recorded
+pub struct ReplayRecorder {
+    pub path: PathBuf,
+    pub replay: Replay,
+}
+
+// Present while a replay is driving the paddle
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    pub frame: usize,
+}
+
+// Where a replay presses the paddle keys, for anything else pressing them to go after
+#[derive(Debug, Clone, PartialEq, Eq, Hash, SystemLabel)]
+pub struct PlayBackInputs;
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(
+            CoreStage::PreUpdate,
+            play_back_inputs.label(PlayBackInputs).after(InputSystem),
+        )
+        .add_system_set(SystemSet::on_enter(GameState::InGame).with_system(start_recording))
+        .add_system_set(SystemSet::on_update(GameState::InGame).with_system(record_inputs))
+        .add_system_set(SystemSet::on_enter(GameState::GameOver).with_system(save_recording))
+        .add_system_to_stage(CoreStage::Last, save_recording_on_exit);
+    }
+}
+
+fn play_back_inputs(
+    mut commands: Commands,
+    player: Option<ResMut<ReplayPlayer>>,
+    app_state: Res<State<GameState>>,
+    mut clock: ResMut<GameClock>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+) {
+    let mut player = match player {
+        Some(player) => player,
+        None => return,
+    };
+
+    if *app_state.current() != GameState::InGame {
+        return;
+    }
+
+    let frame = match player.replay.frames.get(player.frame) {
+        Some(frame) => *frame,
+        None => {
+            info!("replay finished after {} frames", player.frame);
+            for (left_key, right_key) in PADDLE_KEYS {
+                keyboard_input.release(left_key);
+                keyboard_input.release(right_key);
+            }
+            commands.remove_resource::<ReplayPlayer>();
+            return;
+        }
+    };
+    player.frame += 1;
+
+    clock.set_delta(frame.delta);
+    for ((left_key, right_key), (left, right)) in PADDLE_KEYS.into_iter().zip(frame.keys) {
+        for (key, pressed) in [(left_key, left), (right_key, right)] {
+            if pressed {
+                keyboard_input.press(key);
+            } else {
+                keyboard_input.release(key);
+            }
+        }
+    }
+}
+
+// The game may have been set up differently from the main menu since the recorder was created,
+// or by the host of a network game
+fn start_recording(
+    recorder: Option<ResMut<ReplayRecorder>>,
+    game_config: Res<GameConfig>,
+    rng: Res<GameRng>,
+) {
+    if let Some(mut recorder) = recorder {
+        recorder.replay = Replay::new(rng.seed(), &game_config);
+    }
+}
+
+fn record_inputs(
+    recorder: Option<ResMut<ReplayRecorder>>,
+    clock: Res<GameClock>,
+    keyboard_input: Res<Input<KeyCode>>,
+) {
+    if let Some(mut recorder) = recorder {
+        recorder.replay.frames.push(ReplayFrame {
+            delta: clock.delta_seconds(),
+            keys: PADDLE_KEYS.map(|(left_key, right_key)| {
+                (
+                    keyboard_input.pressed(left_key),
+                    keyboard_input.pressed(right_key),
+                )
+            }),
+        });
+    }
+}
+
+// Only the first game of a session is recorded, as later games continue from a different seed
+fn save_recording(mut commands: Commands, recorder: Option<Res<ReplayRecorder>>) {
+    if let Some(recorder) = recorder {
+        match recorder.replay.save(&recorder.path) {
+            Ok(()) => info!("saved replay to {}", recorder.path.display()),
+            Err(err) => warn!(
+                "could not save replay to {}: {}",
+                recorder.path.display(),
+                err
+            ),
+        }
+        commands.remove_resource::<ReplayRecorder>();
+    }
+}
+
+fn save_recording_on_exit(
+    commands: Commands,
+    exit: EventReader<AppExit>,
+    recorder: Option<Res<ReplayRecorder>>,
+) {
+    if !exit.is_empty() {
+        save_recording(commands, recorder);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "breakout-replay 1\nseed 42\nlevel 2\ndifficulty hard\n";
+
+    #[test]
+    fn replays_survive_saving() {
+        let game_config = GameConfig {
+            mode: GameMode::Coop,
+            starting_level: 3,
+            difficulty: Difficulty::Custom,
+            custom_difficulty: DifficultyParams {
+                lives: 7,
+                score_multiplier: 2.5,
+                ..DifficultyParams::default()
+            },
+            speed_up: SpeedUpConfig {
+                top_row: false,
+                step: 0.25,
+                ..SpeedUpConfig::default()
+            },
+            layout: Some(LevelLayout {
+                rows: vec!["#S.X".to_string(), "T".to_string()],
+            }),
+        };
+        let mut replay = Replay::new(7, &game_config);
+        replay.frames = vec![
+            ReplayFrame {
+                delta: 0.016,
+                keys: [(true, false), (false, true)],
+            },
+            ReplayFrame {
+                delta: 0.5,
+                keys: [(false, false), (true, true)],
+            },
+        ];
+
+        let loaded = Replay::parse(&replay.contents()).unwrap();
+        assert_eq!(loaded.mode, replay.mode);
+        assert_eq!(loaded.seed, replay.seed);
+        assert_eq!(loaded.level, replay.level);
+        assert_eq!(loaded.difficulty, replay.difficulty);
+        assert_eq!(loaded.custom_difficulty, replay.custom_difficulty);
+        assert_eq!(loaded.speed_up, replay.speed_up);
+        assert_eq!(loaded.layout, replay.layout);
+        assert_eq!(loaded.frames, replay.frames);
+    }
+
+    #[test]
+    fn level_zero_is_rejected() {
+        let contents = HEADER.replace("level 2", "level 0");
+        assert!(Replay::parse(&contents).is_err());
+    }
+
+    #[test]
+    fn other_headers_are_rejected() {
+        for header in ["breakout-replay 2", "", "seed 42"] {
+            let contents = HEADER.replace("breakout-replay 1", header);
+            assert!(Replay::parse(&contents).is_err(), "{:?}", header);
+        }
+    }
+
+    #[test]
+    fn bad_layout_rows_are_rejected() {
+        let too_wide = format!("layout {}", "#".repeat(BrickGrid::default().n_columns + 1));
+        for row in ["layout ##?#", too_wide.as_str()] {
+            let contents = format!("{}{}\n", HEADER, row);
+            assert!(Replay::parse(&contents).is_err(), "{:?}", row);
+        }
+
+        let contents = format!("{}layout #S.X\n0.016 L-\n", HEADER);
+        assert!(Replay::parse(&contents).is_ok());
+    }
+}
@@ -0,0 +1,79 @@
+use bevy::{prelude::*, render::camera::Camera2d, ui::UiSystem};
+
+// The game is laid out in a fixed logical space of this size, no matter how big the window is.
+// The 2D camera zooms so the whole space stays visible, with bars on the sides when the window
+// has a different aspect ratio.
+pub const LOGICAL_WIDTH: f32 = 800.0;
+pub const LOGICAL_HEIGHT: f32 = 600.0;
+
+// How many window pixels one logical unit takes up
+pub struct ViewScale(pub f32);
+
+// Font size in logical units, the text is resized whenever the window is
+#[derive(Component)]
+pub struct ScaledFont(pub f32);
+
+// Node size in logical units, the node is resized whenever the window is
+#[derive(Component)]
+pub struct ScaledSize(pub Vec2);
+
+pub struct ScalingPlugin;
+
+impl Plugin for ScalingPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ViewScale(1.0))
+            .add_system_to_stage(CoreStage::PreUpdate, update_view_scale)
+            .add_system_to_stage(CoreStage::PostUpdate, fit_camera_to_window)
+            .add_system_to_stage(CoreStage::PostUpdate, scale_fonts.before(UiSystem::Flex))
+            .add_system_to_stage(CoreStage::PostUpdate, scale_nodes.before(UiSystem::Flex));
+    }
+}
+
+fn update_view_scale(windows: Res<Windows>, mut view_scale: ResMut<ViewScale>) {
+    // there's no window to fit when running headless
+    if let Some(window) = windows.get_primary() {
+        let scale = (window.width() / LOGICAL_WIDTH).min(window.height() / LOGICAL_HEIGHT);
+        if view_scale.0 != scale {
+            view_scale.0 = scale;
+        }
+    }
+}
+
+fn fit_camera_to_window(
+    view_scale: Res<ViewScale>,
+    mut camera_query: Query<&mut OrthographicProjection, With<Camera2d>>,
+) {
+    let projection_scale = 1.0 / view_scale.0;
+    for mut projection in camera_query.iter_mut() {
+        if projection.scale != projection_scale {
+            projection.scale = projection_scale;
+        }
+    }
+}
+
+fn scale_fonts(view_scale: Res<ViewScale>, mut text_query: Query<(&mut Text, &ScaledFont)>) {
+    for (mut text, scaled_font) in text_query.iter_mut() {
+        let font_size = (scaled_font.0 * view_scale.0).round();
+        if text
+            .sections
+            .iter()
+            .any(|section| section.style.font_size != font_size)
+        {
+            for section in text.sections.iter_mut() {
+                section.style.font_size = font_size;
+            }
+        }
+    }
+}
+
+fn scale_nodes(view_scale: Res<ViewScale>, mut style_query: Query<(&mut Style, &ScaledSize)>) {
+    for (mut style, scaled_size) in style_query.iter_mut() {
+        let size = Size::new(
+            Val::Px((scaled_size.0.x * view_scale.0).round()),
+            Val::Px((scaled_size.0.y * view_scale.0).round()),
+        );
+        if style.size != size {
+            style.size = size;
+        }
+    }
+}
@@ -0,0 +1,254 @@
+use std::{fs, path::Path};
+
+use bevy::{prelude::*, window::WindowMode};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    game::prelude::{BotSkill, DifficultyParams, SpeedUpConfig},
+    palette::Palette,
+    theme::Themes,
+};
+
+const SETTINGS_PATH: &str = "settings.ron";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+pub enum DisplayMode {
+    Windowed,
+    BorderlessFullscreen,
+    ExclusiveFullscreen,
+}
+
+impl DisplayMode {
+    pub fn window_mode(&self) -> WindowMode {
+        match self {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::BorderlessFullscreen => WindowMode::BorderlessFullscreen,
+            DisplayMode::ExclusiveFullscreen => WindowMode::Fullscreen,
+        }
+    }
+}
+
+// Player preferences, saved to `settings.ron` whenever they change. Missing fields fall back to
+// their defaults so older settings files keep working as options get added.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub display_mode: DisplayMode,
+    // name of a theme from `assets/themes`
+    pub theme: String,
+    pub palette: Palette,
+    // marks the kinds of bricks with patterns, so they can be told apart without colours
+    pub brick_patterns: bool,
+    pub master_volume: f32,
+    pub sfx_volume: f32,
+    pub muted: bool,
+    pub particles: bool,
+    // 0 turns screen shake off
+    pub screen_shake: f32,
+    pub hit_stop: bool,
+    // a bot plays the second paddle of two-player modes
+    pub second_player_bot: bool,
+    // what the Custom difficulty plays like, only editable in the file
+    pub custom_difficulty: DifficultyParams,
+    // how well bots play, only editable in the file
+    pub bot: BotSkill,
+    // when the ball speeds up during a rally, only editable in the file
+    pub speed_up: SpeedUpConfig,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            display_mode: DisplayMode::Windowed,
+            theme: "Classic".to_string(),
+            palette: Palette::Standard,
+            brick_patterns: false,
+            master_volume: 0.8,
+            sfx_volume: 1.0,
+            muted: false,
+            particles: true,
+            screen_shake: 1.0,
+            hit_stop: true,
+            second_player_bot: false,
+            custom_difficulty: DifficultyParams::default(),
+            bot: BotSkill::default(),
+            speed_up: SpeedUpConfig::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        let path = Path::new(SETTINGS_PATH);
+        if !path.exists() {
+            return Settings::default();
+        }
+
+        match fs::read_to_string(path)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| ron::from_str(&contents).map_err(|err| err.to_string()))
+        {
+            Ok(settings) => settings,
+            Err(err) => {
+                warn!("could not read {}, using defaults: {}", SETTINGS_PATH, err);
+                Settings::default()
+            }
+        }
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())
+            .and_then(|contents| fs::write(SETTINGS_PATH, contents).map_err(|err| err.to_string()));
+
+        if let Err(err) = result {
+            warn!("could not save {}: {}", SETTINGS_PATH, err);
+        }
+    }
+}
+
+// The options shown in the settings menu
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SettingsEntry {
+    DisplayMode,
+    Theme,
+    Palette,
+    BrickPatterns,
+    MasterVolume,
+    SfxVolume,
+    Mute,
+    Particles,
+    ScreenShake,
+    HitStop,
+    SecondPlayer,
+}
+
+impl SettingsEntry {
+    pub const ALL: [SettingsEntry; 11] = [
+        SettingsEntry::DisplayMode,
+        SettingsEntry::Theme,
+        SettingsEntry::Palette,
+        SettingsEntry::BrickPatterns,
+        SettingsEntry::MasterVolume,
+        SettingsEntry::SfxVolume,
+        SettingsEntry::Mute,
+        SettingsEntry::Particles,
+        SettingsEntry::ScreenShake,
+        SettingsEntry::HitStop,
+        SettingsEntry::SecondPlayer,
+    ];
+
+    pub fn label(&self, settings: &Settings) -> String {
+        match self {
+            SettingsEntry::DisplayMode => format!(
+                "Display: {}",
+                match settings.display_mode {
+                    DisplayMode::Windowed => "Windowed",
+                    DisplayMode::BorderlessFullscreen => "Borderless",
+                    DisplayMode::ExclusiveFullscreen => "Fullscreen",
+                }
+            ),
+            SettingsEntry::Theme => format!("Theme: {}", settings.theme),
+            SettingsEntry::Palette => format!("Colours: {}", settings.palette.name()),
+            SettingsEntry::BrickPatterns => {
+                format!("Brick patterns: {}", on_off(settings.brick_patterns))
+            }
+            SettingsEntry::MasterVolume => {
+                format!("Master volume: {}%", percent(settings.master_volume))
+            }
+            SettingsEntry::SfxVolume => {
+                format!("Effects volume: {}%", percent(settings.sfx_volume))
+            }
+            SettingsEntry::Mute => format!("Sound: {}", on_off(!settings.muted)),
+            SettingsEntry::Particles => format!("Particles: {}", on_off(settings.particles)),
+            SettingsEntry::ScreenShake if settings.screen_shake <= 0.0 => {
+                "Screen shake: Off".to_string()
+            }
+            SettingsEntry::ScreenShake => {
+                format!("Screen shake: {}%", percent(settings.screen_shake))
+            }
+            SettingsEntry::HitStop => format!("Hit-stop: {}", on_off(settings.hit_stop)),
+            SettingsEntry::SecondPlayer => format!(
+                "Second player: {}",
+                if settings.second_player_bot {
+                    "Bot"
+                } else {
+                    "Human"
+                }
+            ),
+        }
+    }
+
+    // Moves the option to its next (or previous, for a negative step) value
+    pub fn cycle(&self, settings: &mut Settings, themes: &Themes, step: i32) {
+        match self {
+            SettingsEntry::DisplayMode => {
+                const MODES: [DisplayMode; 3] = [
+                    DisplayMode::Windowed,
+                    DisplayMode::BorderlessFullscreen,
+                    DisplayMode::ExclusiveFullscreen,
+                ];
+                settings.display_mode = cycle_value(&MODES, settings.display_mode, step);
+            }
+            SettingsEntry::Theme => {
+                settings.theme = cycle_value(&themes.names(), settings.theme.clone(), step);
+            }
+            SettingsEntry::Palette => {
+                settings.palette = cycle_value(&Palette::ALL, settings.palette, step);
+            }
+            SettingsEntry::BrickPatterns => settings.brick_patterns = !settings.brick_patterns,
+            SettingsEntry::MasterVolume => {
+                settings.master_volume = step_volume(settings.master_volume, step)
+            }
+            SettingsEntry::SfxVolume => {
+                settings.sfx_volume = step_volume(settings.sfx_volume, step)
+            }
+            SettingsEntry::Mute => settings.muted = !settings.muted,
+            SettingsEntry::Particles => settings.particles = !settings.particles,
+            SettingsEntry::ScreenShake => {
+                settings.screen_shake = step_volume(settings.screen_shake, step)
+            }
+            SettingsEntry::HitStop => settings.hit_stop = !settings.hit_stop,
+            SettingsEntry::SecondPlayer => settings.second_player_bot = !settings.second_player_bot,
+        }
+    }
+}
+
+fn percent(volume: f32) -> u32 {
+    (volume * 100.0).round() as u32
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+// Volumes (and other percentages) go up and down in steps of 10%
+fn step_volume(volume: f32, step: i32) -> f32 {
+    ((volume * 10.0).round() + step as f32).clamp(0.0, 10.0) / 10.0
+}
+
+pub fn cycle_value<T: Clone + PartialEq>(values: &[T], current: T, step: i32) -> T {
+    let index = values
+        .iter()
+        .position(|value| *value == current)
+        .unwrap_or(0) as i32;
+    values[(index + step).rem_euclid(values.len() as i32) as usize].clone()
+}
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_to_stage(CoreStage::Last, save_settings);
+    }
+}
+
+fn save_settings(settings: Res<Settings>) {
+    if settings.is_changed() && !settings.is_added() {
+        settings.save();
+    }
+}
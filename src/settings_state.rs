@@ -0,0 +1,106 @@
+use bevy::prelude::*;
+
+use crate::{
+    settings::{Settings, SettingsEntry},
+    state_plugin::StateChange,
+    theme::Themes,
+    utilities::{
+        self, despawn_entities, MenuButtonAction, MenuEntity, MenuEvent, MenuOptions, MenuStyle,
+        SelectedOption, SelectionMarker,
+    },
+    GameState,
+};
+
+pub struct SettingsMenuPlugin;
+
+impl Plugin for SettingsMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(GameState::Settings).with_system(render_menu))
+            .add_system_set(
+                SystemSet::on_update(GameState::Settings)
+                    .with_system(utilities::menu_interaction)
+                    .with_system(select_menu_item)
+                    .with_system(update_labels.after(select_menu_item)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(GameState::Settings).with_system(despawn_entities::<MenuEntity>),
+            );
+    }
+}
+
+fn render_menu(commands: Commands, asset_server: Res<AssetServer>, settings: Res<Settings>) {
+    let mut buttons: Vec<_> = SettingsEntry::ALL
+        .iter()
+        .map(|entry| (entry.label(&settings), MenuButtonAction::Setting(*entry)))
+        .collect();
+    buttons.push(("Back".to_string(), MenuButtonAction::Back));
+
+    utilities::render_menu(
+        commands,
+        asset_server,
+        MenuOptions {
+            title: "Settings",
+            buttons,
+            style: MenuStyle::COMPACT,
+        },
+    );
+}
+
+fn select_menu_item(
+    keyboard_input: Res<Input<KeyCode>>,
+    selected_option_query: Query<&MenuButtonAction, With<SelectedOption>>,
+    mut settings: ResMut<Settings>,
+    themes: Res<Themes>,
+    mut event_state_change: EventWriter<StateChange>,
+    mut menu_events: EventWriter<MenuEvent>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        event_state_change.send(StateChange::Set(GameState::MainMenu));
+        return;
+    }
+
+    let menu_action = match selected_option_query.get_single() {
+        Ok(menu_action) => menu_action,
+        Err(_) => return,
+    };
+
+    match menu_action {
+        MenuButtonAction::Setting(entry) => {
+            if keyboard_input.just_pressed(KeyCode::Left) {
+                entry.cycle(&mut settings, &themes, -1);
+                menu_events.send(MenuEvent::Selected);
+            } else if keyboard_input.just_pressed(KeyCode::Right)
+                || utilities::confirm_pressed(&keyboard_input)
+            {
+                entry.cycle(&mut settings, &themes, 1);
+                menu_events.send(MenuEvent::Selected);
+            }
+        }
+        MenuButtonAction::Back if utilities::confirm_pressed(&keyboard_input) => {
+            menu_events.send(MenuEvent::Selected);
+            event_state_change.send(StateChange::Set(GameState::MainMenu));
+        }
+        _ => {}
+    }
+}
+
+// Keeps the button text in sync with the values, which can also change through shortcuts
+fn update_labels(
+    settings: Res<Settings>,
+    buttons_query: Query<(&MenuButtonAction, &Children)>,
+    mut text_query: Query<&mut Text, Without<SelectionMarker>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    for (menu_action, children) in buttons_query.iter() {
+        if let MenuButtonAction::Setting(entry) = menu_action {
+            for child in children.iter() {
+                if let Ok(mut text) = text_query.get_mut(*child) {
+                    text.sections[0].value = entry.label(&settings);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use bevy::{audio::PlaybackSettings, prelude::*};
+
+use crate::{
+    game::prelude::{
+        BallHitPaddle, BallHitWall, BallLost, BrickDestroyed, BrickHit, BrickKind, LevelCleared,
+    },
+    scaling::LOGICAL_HEIGHT,
+    settings::Settings,
+    utilities::MenuEvent,
+};
+
+// How much higher bricks at the top of the arena sound compared to the ones at the bottom
+const BRICK_PITCH_RANGE: f32 = 0.5;
+
+struct Sounds {
+    paddle_hit: Handle<AudioSource>,
+    wall_hit: Handle<AudioSource>,
+    brick_break: Handle<AudioSource>,
+    life_lost: Handle<AudioSource>,
+    level_clear: Handle<AudioSource>,
+    menu_move: Handle<AudioSource>,
+    menu_select: Handle<AudioSource>,
+}
+
+// Plays sound effects in response to gameplay and menu events
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_sounds)
+            .add_system(toggle_mute)
+            .add_system(play_game_sounds)
+            .add_system(play_menu_sounds);
+    }
+}
+
+fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Sounds {
+        paddle_hit: asset_server.load("sounds/paddle_hit.wav"),
+        wall_hit: asset_server.load("sounds/wall_hit.wav"),
+        brick_break: asset_server.load("sounds/brick_break.wav"),
+        life_lost: asset_server.load("sounds/life_lost.wav"),
+        level_clear: asset_server.load("sounds/level_clear.wav"),
+        menu_move: asset_server.load("sounds/menu_move.wav"),
+        menu_select: asset_server.load("sounds/menu_select.wav"),
+    });
+}
+
+fn toggle_mute(keyboard_input: Res<Input<KeyCode>>, mut settings: ResMut<Settings>) {
+    if keyboard_input.just_pressed(KeyCode::M) {
+        settings.muted = !settings.muted;
+    }
+}
+
+fn play(audio: &Audio, settings: &Settings, sound: &Handle<AudioSource>, speed: f32) {
+    let volume = settings.master_volume * settings.sfx_volume;
+    if settings.muted || volume <= 0.0 {
+        return;
+    }
+
+    audio.play_with_settings(
+        sound.clone(),
+        PlaybackSettings {
+            volume,
+            speed,
+            ..default()
+        },
+    );
+}
+
+#[allow(clippy::too_many_arguments)]
+fn play_game_sounds(
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+    mut paddle_hits: EventReader<BallHitPaddle>,
+    mut wall_hits: EventReader<BallHitWall>,
+    mut brick_hits: EventReader<BrickHit>,
+    mut bricks_destroyed: EventReader<BrickDestroyed>,
+    mut balls_lost: EventReader<BallLost>,
+    mut levels_cleared: EventReader<LevelCleared>,
+) {
+    for _ in paddle_hits.iter() {
+        play(&audio, &settings, &sounds.paddle_hit, 1.0);
+    }
+
+    for _ in wall_hits.iter() {
+        play(&audio, &settings, &sounds.wall_hit, 1.0);
+    }
+
+    // bricks that hold out knock, lower for strong ones and higher for steel
+    for hit in brick_hits.iter() {
+        let speed = if hit.kind == BrickKind::Steel {
+            1.4
+        } else {
+            0.8
+        };
+        play(&audio, &settings, &sounds.wall_hit, speed);
+    }
+
+    for brick in bricks_destroyed.iter() {
+        // the higher up the brick, the higher the pitch, with strong bricks sounding heavier
+        let height = (brick.position.y / LOGICAL_HEIGHT + 0.5).clamp(0.0, 1.0);
+        let weight = if brick.kind == BrickKind::Strong {
+            0.8
+        } else {
+            1.0
+        };
+        play(
+            &audio,
+            &settings,
+            &sounds.brick_break,
+            (1.0 + height * BRICK_PITCH_RANGE) * weight,
+        );
+    }
+
+    for _ in balls_lost.iter() {
+        play(&audio, &settings, &sounds.life_lost, 1.0);
+    }
+
+    for _ in levels_cleared.iter() {
+        play(&audio, &settings, &sounds.level_clear, 1.0);
+    }
+}
+
+fn play_menu_sounds(
+    audio: Res<Audio>,
+    sounds: Res<Sounds>,
+    settings: Res<Settings>,
+    mut menu_events: EventReader<MenuEvent>,
+) {
+    for menu_event in menu_events.iter() {
+        let sound = match menu_event {
+            MenuEvent::Moved => &sounds.menu_move,
+            MenuEvent::Selected => &sounds.menu_select,
+        };
+        play(&audio, &settings, sound, 1.0);
+    }
+}
@@ -0,0 +1,250 @@
+use std::{
+    io::{ErrorKind, Write},
+    net::{TcpListener, TcpStream},
+};
+
+use bevy::prelude::*;
+use serde::Serialize;
+
+use crate::{
+    game::prelude::{Ball, Brick, GameConfig, GameData, Paddle, Velocity},
+    GameState,
+};
+
+pub const DEFAULT_SPECTATOR_PORT: u16 = 7878;
+// A client that can't keep up misses snapshots rather than making the game buffer them
+const MAX_PENDING_BYTES: usize = 64 * 1024;
+
+// Port the spectator server listens on, from the command line
+pub struct SpectatorPort(pub u16);
+
+#[derive(Default)]
+struct SpectatorServer {
+    listener: Option<TcpListener>,
+    clients: Vec<SpectatorClient>,
+    tick: u64,
+}
+
+struct SpectatorClient {
+    stream: TcpStream,
+    // the part of the snapshots not written to the connection yet
+    pending: Vec<u8>,
+}
+
+impl SpectatorClient {
+    // Writes as much as the connection takes without blocking, false once it's closed
+    fn flush(&mut self) -> bool {
+        while !self.pending.is_empty() {
+            match self.stream.write(&self.pending) {
+                Ok(0) => return false,
+                Ok(written) => {
+                    self.pending.drain(..written);
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => return true,
+                Err(_) => return false,
+            }
+        }
+        true
+    }
+}
+
+// What a spectator sees every frame, sent as one line of JSON
+#[derive(Serialize)]
+struct Snapshot {
+    tick: u64,
+    state: String,
+    mode: &'static str,
+    score: i32,
+    level: u32,
+    lives: u32,
+    // only while a game is on
+    ball: Option<BallState>,
+    paddles: Vec<PaddleState>,
+    bricks: Vec<BrickState>,
+}
+
+#[derive(Serialize)]
+struct BallState {
+    x: f32,
+    y: f32,
+    vx: f32,
+    vy: f32,
+}
+
+#[derive(Serialize)]
+struct PaddleState {
+    player: usize,
+    x: f32,
+    y: f32,
+    width: f32,
+}
+
+#[derive(Serialize)]
+struct BrickState {
+    x: f32,
+    y: f32,
+    kind: String,
+    hits_left: u32,
+}
+
+// Streams the game to any number of local TCP clients as newline-separated JSON snapshots, one
+// per frame, for overlays and dashboards. Clients only listen, anything they send is ignored.
+pub struct SpectatorPlugin;
+
+impl Plugin for SpectatorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpectatorServer>()
+            .add_startup_system(start_server)
+            .add_system_to_stage(CoreStage::Last, stream_snapshot);
+    }
+}
+
+fn start_server(port: Res<SpectatorPort>, mut server: ResMut<SpectatorServer>) {
+    // only spectators on this machine, a dashboard elsewhere can go through a tunnel
+    let listener = TcpListener::bind(("127.0.0.1", port.0))
+        .and_then(|listener| listener.set_nonblocking(true).map(|_| listener));
+    match listener {
+        Ok(listener) => {
+            if let Ok(address) = listener.local_addr() {
+                info!("spectator server listening on {}", address);
+            }
+            server.listener = Some(listener);
+        }
+        Err(err) => warn!("could not start the spectator server: {}", err),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn stream_snapshot(
+    mut server: ResMut<SpectatorServer>,
+    app_state: Res<State<GameState>>,
+    game_config: Res<GameConfig>,
+    game_data: Res<GameData>,
+    ball_query: Query<(&Transform, &Velocity), With<Ball>>,
+    paddle_query: Query<(&Paddle, &Transform)>,
+    brick_query: Query<(&Brick, &Transform)>,
+) {
+    let server = &mut *server;
+    if let Some(listener) = &server.listener {
+        while let Ok((stream, address)) = listener.accept() {
+            if stream.set_nonblocking(true).is_ok() {
+                info!("spectator connected from {}", address);
+                let _ = stream.set_nodelay(true);
+                server.clients.push(SpectatorClient {
+                    stream,
+                    pending: Vec::new(),
+                });
+            }
+        }
+    }
+
+    server.tick += 1;
+    if server.clients.is_empty() {
+        return;
+    }
+
+    let mut paddles: Vec<_> = paddle_query
+        .iter()
+        .map(|(paddle, transform)| PaddleState {
+            player: paddle.player,
+            x: transform.translation.x,
+            y: transform.translation.y,
+            width: transform.scale.x,
+        })
+        .collect();
+    paddles.sort_by_key(|paddle| paddle.player);
+
+    let snapshot = Snapshot {
+        tick: server.tick,
+        state: format!("{:?}", app_state.current()),
+        mode: game_config.mode.name(),
+        score: game_data.score,
+        level: game_data.level,
+        lives: game_data.lives,
+        ball: ball_query
+            .iter()
+            .next()
+            .map(|(transform, velocity)| BallState {
+                x: transform.translation.x,
+                y: transform.translation.y,
+                vx: velocity.x,
+                vy: velocity.y,
+            }),
+        paddles,
+        bricks: brick_query
+            .iter()
+            .map(|(brick, transform)| BrickState {
+                x: transform.translation.x,
+                y: transform.translation.y,
+                kind: format!("{:?}", brick.kind),
+                hits_left: brick.hits_left,
+            })
+            .collect(),
+    };
+    let mut line = match serde_json::to_vec(&snapshot) {
+        Ok(line) => line,
+        Err(err) => {
+            warn!("could not encode the game state: {}", err);
+            return;
+        }
+    };
+    line.push(b'\n');
+
+    server.clients.retain_mut(|client| {
+        if client.pending.len() < MAX_PENDING_BYTES {
+            client.pending.extend_from_slice(&line);
+        }
+        let connected = client.flush();
+        if !connected {
+            info!("spectator disconnected");
+        }
+        connected
+    });
+}
+
+#[cfg(all(test, feature = "spectator"))]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        time::Duration,
+    };
+
+    use super::*;
+
+    #[test]
+    fn clients_get_the_game_state() {
+        let mut app = App::new();
+        app.insert_resource(SpectatorPort(0))
+            .insert_resource(State::new(GameState::InGame))
+            .insert_resource(GameConfig::default())
+            .insert_resource(GameData {
+                score: 12,
+                level: 2,
+                lives: 3,
+            })
+            .add_plugin(SpectatorPlugin);
+        app.update();
+
+        let address = app
+            .world
+            .resource::<SpectatorServer>()
+            .listener
+            .as_ref()
+            .and_then(|listener| listener.local_addr().ok())
+            .expect("the server is listening");
+        let stream = TcpStream::connect(address).unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        app.update();
+
+        let mut line = String::new();
+        BufReader::new(stream).read_line(&mut line).unwrap();
+        let snapshot: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(snapshot["state"], "InGame");
+        assert_eq!(snapshot["score"], 12);
+        assert_eq!(snapshot["level"], 2);
+        assert_eq!(snapshot["lives"], 3);
+        assert!(snapshot["ball"].is_null());
+    }
+}
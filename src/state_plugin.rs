@@ -7,6 +7,8 @@ pub enum StateChange {
     Set(GameState),
     Push(GameState),
     Pop,
+    // leaves every state on the stack, not just the one on top
+    Replace(GameState),
     Exit,
 }
 
@@ -28,6 +30,7 @@ fn handle_state_change(
             StateChange::Push(state) => app_state.push(*state).unwrap(),
             StateChange::Pop => app_state.pop().unwrap(),
             StateChange::Set(state) => app_state.set(*state).unwrap(),
+            StateChange::Replace(state) => app_state.replace(*state).unwrap(),
             StateChange::Exit => exit.send(AppExit),
         }
     }
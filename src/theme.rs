@@ -0,0 +1,266 @@
+use std::{fs, path::Path};
+
+use bevy::{
+    asset::{AssetServerSettings, FileAssetIo},
+    prelude::*,
+    render::texture::DEFAULT_IMAGE_HANDLE,
+};
+use serde::Deserialize;
+
+use crate::{game::prelude::BrickKind, settings::Settings};
+
+const STRONG_BRICK_PATTERN: &str = "textures/pattern_strong.png";
+const STEEL_BRICK_PATTERN: &str = "textures/pattern_steel.png";
+const TIME_BRICK_PATTERN: &str = "textures/pattern_time.png";
+
+// Relative to the asset folder, like the textures and sounds
+const THEMES_DIR: &str = "themes";
+
+// How one element of the game looks in a theme file. The texture is tinted by the colour, so
+// white textures take on any colour.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ElementDefinition {
+    pub color: (f32, f32, f32),
+    #[serde(default)]
+    pub texture: Option<String>,
+}
+
+// A theme file from `assets/themes`
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThemeDefinition {
+    pub name: String,
+    pub background: (f32, f32, f32),
+    pub ball: ElementDefinition,
+    pub paddle: ElementDefinition,
+    pub wall: ElementDefinition,
+    pub brick: ElementDefinition,
+    pub strong_brick: ElementDefinition,
+    pub steel_brick: ElementDefinition,
+    // themes from before time bricks existed get the fallback theme's
+    #[serde(default = "ElementDefinition::time_brick")]
+    pub time_brick: ElementDefinition,
+}
+
+impl ElementDefinition {
+    fn time_brick() -> Self {
+        ElementDefinition {
+            color: (0.35, 0.65, 0.75),
+            texture: None,
+        }
+    }
+}
+
+impl ThemeDefinition {
+    // Used when no theme files can be found, so the game still looks like itself
+    pub fn fallback() -> Self {
+        let element = |r, g, b| ElementDefinition {
+            color: (r, g, b),
+            texture: None,
+        };
+        ThemeDefinition {
+            name: "Classic".to_string(),
+            background: (0.2, 0.2, 0.2),
+            ball: element(1.0, 0.5, 0.5),
+            paddle: element(0.3, 0.3, 0.7),
+            wall: element(0.8, 0.8, 0.8),
+            brick: element(0.6, 0.5, 0.4),
+            strong_brick: element(0.75, 0.35, 0.25),
+            steel_brick: element(0.55, 0.6, 0.65),
+            time_brick: ElementDefinition::time_brick(),
+        }
+    }
+}
+
+// All the themes that can be picked in the settings, sorted by file name
+pub struct Themes(pub Vec<ThemeDefinition>);
+
+impl Themes {
+    // Reads the themes from the asset folder, found the same way the asset server finds it so the
+    // game doesn't have to be started from the project directory
+    pub fn load(asset_folder: &str) -> Self {
+        let dir = FileAssetIo::get_root_path()
+            .join(asset_folder)
+            .join(THEMES_DIR);
+        let mut paths: Vec<_> = match fs::read_dir(&dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+                .collect(),
+            Err(err) => {
+                warn!("could not read {}: {}", dir.display(), err);
+                Vec::new()
+            }
+        };
+        paths.sort();
+
+        let mut themes: Vec<_> = paths.iter().filter_map(|path| load_theme(path)).collect();
+        if themes.is_empty() {
+            themes.push(ThemeDefinition::fallback());
+        }
+        Themes(themes)
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.0.iter().map(|theme| theme.name.clone()).collect()
+    }
+
+    // Falls back to the first theme when the one from the settings doesn't exist (anymore)
+    fn get(&self, name: &str) -> &ThemeDefinition {
+        self.0
+            .iter()
+            .find(|theme| theme.name == name)
+            .unwrap_or(&self.0[0])
+    }
+}
+
+fn load_theme(path: &Path) -> Option<ThemeDefinition> {
+    match fs::read_to_string(path)
+        .map_err(|err| err.to_string())
+        .and_then(|contents| ron::from_str(&contents).map_err(|err| err.to_string()))
+    {
+        Ok(theme) => Some(theme),
+        Err(err) => {
+            warn!("could not read theme {}: {}", path.display(), err);
+            None
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ThemeElement {
+    pub color: Color,
+    pub texture: Handle<Image>,
+}
+
+impl ThemeElement {
+    // Sprites are one unit wide and get their size from the transform's scale
+    pub fn sprite_bundle(&self, transform: Transform) -> SpriteBundle {
+        SpriteBundle {
+            sprite: Sprite {
+                color: self.color,
+                custom_size: Some(Vec2::ONE),
+                ..default()
+            },
+            texture: self.texture.clone(),
+            transform,
+            ..default()
+        }
+    }
+}
+
+// The theme currently in use, picked from `Themes` by the settings
+pub struct Theme {
+    pub ball: ThemeElement,
+    pub paddle: ThemeElement,
+    pub wall: ThemeElement,
+    brick: ThemeElement,
+    strong_brick: ThemeElement,
+    steel_brick: ThemeElement,
+    time_brick: ThemeElement,
+    // overlays for the bricks, when turned on in the settings
+    strong_brick_pattern: Option<Handle<Image>>,
+    steel_brick_pattern: Option<Handle<Image>>,
+    time_brick_pattern: Option<Handle<Image>>,
+}
+
+impl Theme {
+    pub fn brick(&self, kind: BrickKind, hits_left: u32) -> &ThemeElement {
+        match kind {
+            BrickKind::Strong if hits_left > 1 => &self.strong_brick,
+            BrickKind::Steel => &self.steel_brick,
+            BrickKind::Time => &self.time_brick,
+            _ => &self.brick,
+        }
+    }
+
+    pub fn brick_pattern(&self, kind: BrickKind, hits_left: u32) -> Option<&Handle<Image>> {
+        match kind {
+            BrickKind::Strong if hits_left > 1 => self.strong_brick_pattern.as_ref(),
+            BrickKind::Steel => self.steel_brick_pattern.as_ref(),
+            BrickKind::Time => self.time_brick_pattern.as_ref(),
+            _ => None,
+        }
+    }
+}
+
+// Loads the themes and keeps the active one in sync with the settings, including the colour
+// palette. Only new entities pick up a change, which is fine as they are made from the main menu.
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        // whoever builds the app can pick the themes instead, the learning environment doesn't
+        // want them read from disk on every reset
+        if !app.world.contains_resource::<Themes>() {
+            let asset_folder = app.world.get_resource::<AssetServerSettings>().map_or_else(
+                || "assets".to_string(),
+                |settings| settings.asset_folder.clone(),
+            );
+            app.insert_resource(Themes::load(&asset_folder));
+        }
+        app.add_system_to_stage(CoreStage::PreUpdate, apply_theme);
+    }
+}
+
+fn apply_theme(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    themes: Res<Themes>,
+    asset_server: Res<AssetServer>,
+    images: Option<Res<Assets<Image>>>,
+) {
+    if !settings.is_changed() {
+        return;
+    }
+
+    let definition = themes.get(&settings.theme);
+    // headless runs have no renderer to load textures for
+    let load_texture = |path: &str| images.as_ref().map(|_| asset_server.load(path));
+    let element = |element: &ElementDefinition| {
+        let (r, g, b) = element.color;
+        ThemeElement {
+            color: Color::rgb(r, g, b),
+            texture: element
+                .texture
+                .as_deref()
+                .and_then(load_texture)
+                .unwrap_or_else(|| DEFAULT_IMAGE_HANDLE.typed()),
+        }
+    };
+    let pattern = |path| {
+        if settings.brick_patterns {
+            load_texture(path)
+        } else {
+            None
+        }
+    };
+
+    let mut theme = Theme {
+        ball: element(&definition.ball),
+        paddle: element(&definition.paddle),
+        wall: element(&definition.wall),
+        brick: element(&definition.brick),
+        strong_brick: element(&definition.strong_brick),
+        steel_brick: element(&definition.steel_brick),
+        time_brick: element(&definition.time_brick),
+        strong_brick_pattern: pattern(STRONG_BRICK_PATTERN),
+        steel_brick_pattern: pattern(STEEL_BRICK_PATTERN),
+        time_brick_pattern: pattern(TIME_BRICK_PATTERN),
+    };
+    let (r, g, b) = definition.background;
+    let mut background = Color::rgb(r, g, b);
+
+    if let Some(colors) = settings.palette.colors() {
+        background = colors.background;
+        theme.ball.color = colors.ball;
+        theme.paddle.color = colors.paddle;
+        theme.wall.color = colors.wall;
+        theme.brick.color = colors.brick;
+        theme.strong_brick.color = colors.strong_brick;
+        theme.steel_brick.color = colors.steel_brick;
+        theme.time_brick.color = colors.time_brick;
+    }
+
+    commands.insert_resource(theme);
+    commands.insert_resource(ClearColor(background));
+}
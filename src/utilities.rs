@@ -1,8 +1,50 @@
-use bevy::prelude::*;
+use bevy::{math::const_vec2, prelude::*};
+
+use crate::{
+    display::alt_pressed,
+    scaling::{ScaledFont, ScaledSize},
+    settings::SettingsEntry,
+};
 
 pub const TEXT_COLOR: Color = Color::WHITE;
 pub const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
 pub const SELECTED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
+// Distance between the selection markers and the sides of the button
+const MARKER_INSET: f32 = 12.0;
+
+// Sizes of the parts of a menu, in logical units (see `scaling`)
+pub struct MenuStyle {
+    pub title_font_size: f32,
+    pub button_size: Vec2,
+    pub button_font_size: f32,
+    pub button_margin: f32,
+}
+
+impl MenuStyle {
+    // A few big buttons, for the main menus
+    pub const LARGE: MenuStyle = MenuStyle {
+        title_font_size: 120.0,
+        button_size: const_vec2!([400.0, 90.0]),
+        button_font_size: 70.0,
+        button_margin: 15.0,
+    };
+
+    // A few more buttons than fit in the large style
+    pub const MEDIUM: MenuStyle = MenuStyle {
+        title_font_size: 70.0,
+        button_size: const_vec2!([440.0, 42.0]),
+        button_font_size: 30.0,
+        button_margin: 4.0,
+    };
+
+    // Many small rows, for lists of options
+    pub const COMPACT: MenuStyle = MenuStyle {
+        title_font_size: 70.0,
+        button_size: const_vec2!([460.0, 33.0]),
+        button_font_size: 26.0,
+        button_margin: 3.0,
+    };
+}
 
 #[derive(Component)]
 pub struct MenuEntity;
@@ -11,39 +53,64 @@ pub struct MenuEntity;
 #[derive(Component)]
 pub struct SelectedOption;
 
+// Arrows on both sides of the selected button, so the selection doesn't rely on colour alone
+#[derive(Component)]
+pub struct SelectionMarker;
+
+// Position of a button in its menu, from top to bottom
+#[derive(Component)]
+pub struct MenuOrder(pub usize);
+
 // All actions that can be triggered from a button click
 #[derive(Component)]
 pub enum MenuButtonAction {
     Play,
+    Mode,
+    Difficulty,
+    Host,
+    Editor,
+    Settings,
+    Setting(SettingsEntry),
+    Back,
     Quit,
 }
 
+// Sent when the player moves through or activates a menu, so it can be given some feedback
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MenuEvent {
+    Moved,
+    Selected,
+}
+
 pub struct MenuOptions<'a> {
     pub title: &'a str,
-    pub play_text: &'a str,
+    pub buttons: Vec<(String, MenuButtonAction)>,
+    pub style: MenuStyle,
 }
 
 pub fn despawn_entities<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
     query.for_each(|entity| commands.entity(entity).despawn_recursive());
 }
 
+// Enter confirms the selected menu item, unless it's part of the Alt+Enter fullscreen shortcut
+pub fn confirm_pressed(keyboard_input: &Input<KeyCode>) -> bool {
+    keyboard_input.just_pressed(KeyCode::Return) && !alt_pressed(keyboard_input)
+}
+
 pub fn render_menu(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
-    windows: Res<Windows>,
     menu_options: MenuOptions,
 ) {
-    let window = windows.get_primary().unwrap();
-    let window_height = window.height();
-    let window_width = window.width();
     let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let menu_style = menu_options.style;
 
     let button_style = Style {
         size: Size::new(
-            Val::Px((window_width / 2.0).round()),
-            Val::Px((window_height / 5.0).round()),
+            Val::Px(menu_style.button_size.x),
+            Val::Px(menu_style.button_size.y),
         ),
-        margin: Rect::all(Val::Px(20.0)),
+        margin: Rect::all(Val::Px(menu_style.button_margin)),
         justify_content: JustifyContent::Center,
         align_items: AlignItems::Center,
         ..default()
@@ -51,7 +118,7 @@ pub fn render_menu(
 
     let button_text_style = TextStyle {
         font: font.clone(),
-        font_size: (window_height / 5.5).round(),
+        font_size: menu_style.button_font_size,
         color: TEXT_COLOR,
     };
 
@@ -72,94 +139,141 @@ pub fn render_menu(
         })
         .insert(MenuEntity)
         .with_children(|parent| {
-            parent.spawn_bundle(TextBundle {
-                style: Style {
-                    margin: Rect::all(Val::Px(30.0)),
-                    ..default()
-                },
-                text: Text::with_section(
-                    menu_options.title,
-                    TextStyle {
-                        font: font.clone(),
-                        font_size: (window_height / 4.0).round(),
-                        color: TEXT_COLOR,
+            parent
+                .spawn_bundle(TextBundle {
+                    style: Style {
+                        margin: Rect::all(Val::Px(30.0)),
+                        ..default()
                     },
-                    Default::default(),
-                ),
-                ..default()
-            });
+                    text: Text::with_section(
+                        menu_options.title,
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: menu_style.title_font_size,
+                            color: TEXT_COLOR,
+                        },
+                        Default::default(),
+                    ),
+                    ..default()
+                })
+                .insert(ScaledFont(menu_style.title_font_size));
 
-            parent
-                .spawn_bundle(ButtonBundle {
+            for (index, (text, action)) in menu_options.buttons.into_iter().enumerate() {
+                let mut button = parent.spawn_bundle(ButtonBundle {
                     style: button_style.clone(),
-                    color: SELECTED_BUTTON.into(),
+                    color: if index == 0 {
+                        SELECTED_BUTTON.into()
+                    } else {
+                        NORMAL_BUTTON.into()
+                    },
                     ..default()
-                })
-                .insert(MenuButtonAction::Play)
-                .insert(SelectedOption)
-                .with_children(|parent| {
-                    parent.spawn_bundle(TextBundle {
-                        text: Text::with_section(
-                            menu_options.play_text,
-                            button_text_style.clone(),
-                            Default::default(),
-                        ),
-                        ..default()
-                    });
                 });
 
-            parent
-                .spawn_bundle(ButtonBundle {
-                    style: button_style,
-                    color: NORMAL_BUTTON.into(),
-                    ..default()
-                })
-                .insert(MenuButtonAction::Quit)
-                .with_children(|parent| {
-                    parent.spawn_bundle(TextBundle {
-                        text: Text::with_section("Quit", button_text_style, Default::default()),
-                        ..default()
+                button
+                    .insert(action)
+                    .insert(MenuOrder(index))
+                    .insert(ScaledSize(menu_style.button_size))
+                    .with_children(|parent| {
+                        parent
+                            .spawn_bundle(TextBundle {
+                                text: Text::with_section(
+                                    text,
+                                    button_text_style.clone(),
+                                    Default::default(),
+                                ),
+                                ..default()
+                            })
+                            .insert(ScaledFont(menu_style.button_font_size));
+
+                        for (marker, position) in [
+                            (
+                                ">",
+                                Rect {
+                                    left: Val::Px(MARKER_INSET),
+                                    ..default()
+                                },
+                            ),
+                            (
+                                "<",
+                                Rect {
+                                    right: Val::Px(MARKER_INSET),
+                                    ..default()
+                                },
+                            ),
+                        ] {
+                            parent
+                                .spawn_bundle(TextBundle {
+                                    style: Style {
+                                        position_type: PositionType::Absolute,
+                                        position,
+                                        ..default()
+                                    },
+                                    text: Text::with_section(
+                                        marker,
+                                        button_text_style.clone(),
+                                        Default::default(),
+                                    ),
+                                    visibility: Visibility {
+                                        is_visible: index == 0,
+                                    },
+                                    ..default()
+                                })
+                                .insert(SelectionMarker)
+                                .insert(ScaledFont(menu_style.button_font_size));
+                        }
                     });
-                });
+
+                // the first button starts out selected
+                if index == 0 {
+                    button.insert(SelectedOption);
+                }
+            }
         });
 }
 
 pub fn menu_interaction(
     mut commands: Commands,
     keyboard_input: Res<Input<KeyCode>>,
-    mut buttons_query: Query<(Entity, &mut UiColor, Option<&SelectedOption>), With<Button>>,
+    mut menu_events: EventWriter<MenuEvent>,
+    mut buttons_query: Query<
+        (Entity, &MenuOrder, &mut UiColor, Option<&SelectedOption>),
+        With<Button>,
+    >,
+    mut markers_query: Query<(&Parent, &mut Visibility), With<SelectionMarker>>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Up) {
-        let mut removed_selected = false;
-        for (button_entity, mut button_color, selected_option) in buttons_query.iter_mut() {
-            if removed_selected {
-                commands.entity(button_entity).insert(SelectedOption);
-                *button_color = SELECTED_BUTTON.into();
-                return;
-            }
+    let step: isize = if keyboard_input.just_pressed(KeyCode::Up) {
+        -1
+    } else if keyboard_input.just_pressed(KeyCode::Down) {
+        1
+    } else {
+        return;
+    };
 
-            if selected_option.is_some() {
-                *button_color = NORMAL_BUTTON.into();
-                commands.entity(button_entity).remove::<SelectedOption>();
-                removed_selected = true;
-            }
-        }
+    let mut buttons: Vec<_> = buttons_query.iter_mut().collect();
+    if buttons.is_empty() {
+        return;
     }
+    buttons.sort_by_key(|(_, order, _, _)| order.0);
 
-    if keyboard_input.just_pressed(KeyCode::Down) {
-        let mut removed_selected = false;
-        for (button_entity, mut button_color, selected_option) in buttons_query.iter_mut() {
-            if removed_selected {
-                commands.entity(button_entity).insert(SelectedOption);
-                *button_color = SELECTED_BUTTON.into();
-                return;
-            }
+    let selected = buttons
+        .iter()
+        .position(|(_, _, _, selected_option)| selected_option.is_some())
+        .unwrap_or(0);
+    let next = (selected as isize + step).clamp(0, buttons.len() as isize - 1) as usize;
 
-            if selected_option.is_some() {
-                *button_color = NORMAL_BUTTON.into();
-                commands.entity(button_entity).remove::<SelectedOption>();
-                removed_selected = true;
-            }
+    if next != selected {
+        let (selected_entity, _, selected_color, _) = &mut buttons[selected];
+        commands.entity(*selected_entity).remove::<SelectedOption>();
+        **selected_color = NORMAL_BUTTON.into();
+
+        let (next_entity, _, next_color, _) = &mut buttons[next];
+        commands.entity(*next_entity).insert(SelectedOption);
+        **next_color = SELECTED_BUTTON.into();
+
+        for (parent, mut visibility) in markers_query.iter_mut() {
+            visibility.is_visible = parent.0 == *next_entity;
         }
+
+        menu_events.send(MenuEvent::Moved);
     }
 }